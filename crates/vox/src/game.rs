@@ -10,17 +10,23 @@ use winit_window_control::main_loop::{
     main_loop_run, Game, InitResult, RenderResult, UpdateResult,
 };
 use vox_render::renderer::vertex::Vertex;
+use vox_world::controls::Action;
+use vox_world::world::level_data::LevelData;
 
 pub struct VoxGame {
     personal_world: Option<PersonalWorld>,
     renderer: Option<Renderer>,
+    level: Option<LevelData>,
+    save_dir: String,
 }
 
 impl VoxGame {
-    pub fn new() -> VoxGame {
+    pub fn new(level: LevelData, save_dir: &str) -> VoxGame {
         VoxGame {
             personal_world: None,
             renderer: None,
+            level: Some(level),
+            save_dir: save_dir.to_string(),
         }
     }
     pub fn run(self) {
@@ -40,6 +46,9 @@ impl Game for VoxGame {
         pw.ui
             .debug_info
             .set_numbers("player z".to_string(), pw.player.position.z as f64);
+        pw.ui
+            .debug_info
+            .set_numbers("selected block".to_string(), pw.player.selected_block as f64);
         pw.ui.debug_info.set_numbers(
             "amount of renderable chunks".to_string(),
             pw.chunk_render_data.len() as f64,
@@ -48,10 +57,56 @@ impl Game for VoxGame {
             "amount of chunks".to_string(),
             pw.world.count_chunks() as f64,
         );
+        let gen_metrics = pw.chunk_gen_pool.metrics();
+        pw.ui.debug_info.set_numbers(
+            "chunk gen queue depth".to_string(),
+            gen_metrics.queue_depth as f64,
+        );
+        pw.ui.debug_info.set_numbers(
+            "chunk gen avg queue latency".to_string(),
+            gen_metrics.average_queue_latency as f64,
+        );
+        pw.ui.debug_info.set_numbers(
+            "chunk gen avg total latency".to_string(),
+            gen_metrics.average_total_latency as f64,
+        );
+        pw.ui.debug_info.set_numbers(
+            "chunk gen cancelled".to_string(),
+            gen_metrics.cancelled as f64,
+        );
+        pw.ui.debug_info.set_numbers(
+            "chunk gen failed".to_string(),
+            gen_metrics.failed as f64,
+        );
+        let io_metrics = pw.chunk_io.metrics();
+        pw.ui.debug_info.set_numbers(
+            "chunk io pending saves".to_string(),
+            io_metrics.pending_saves as f64,
+        );
+        pw.ui.debug_info.set_numbers(
+            "chunk io failed saves".to_string(),
+            io_metrics.failed as f64,
+        );
+        pw.ui.debug_info.set_numbers(
+            "chunk io avg load time".to_string(),
+            io_metrics.average_load_time as f64,
+        );
+        pw.ui.debug_info.set_numbers(
+            "chunk io avg save time".to_string(),
+            io_metrics.average_save_time as f64,
+        );
+        pw.ui.debug_info.set_numbers(
+            "save compression ratio".to_string(),
+            pw.world.save_stats.ratio() as f64,
+        );
+        pw.ui.debug_info.set_numbers(
+            "save compression time".to_string(),
+            pw.world.save_stats.time as f64,
+        );
 
         let timer = Instant::now();
 
-        pw.on_game_tick(0.1);
+        pw.on_game_tick(dt as f32);
         pw.ui
             .debug_info
             .insert_stat("world tick".to_string(), timer.elapsed().as_secs_f32());
@@ -63,7 +118,7 @@ impl Game for VoxGame {
         //wgpu_state.resize(physical_size);
 
     }
-    fn on_render(&mut self, input: &mut Input, dt: f64, window: &Window) -> RenderResult {
+    fn on_render(&mut self, input: &mut Input, _dt: f64, window: &Window) -> RenderResult {
         let timer = Instant::now();
         let pw = self.personal_world.as_mut().unwrap();
         let timer = Instant::now();
@@ -100,8 +155,14 @@ impl Game for VoxGame {
         }
 
         pw.update_ui_input(&input);
-        pw.player.handle_input(&input, &(dt as f32), &pw.world);
+        pw.controls.update(&input);
+        if pw.controls.actions.held(Action::Exit) {
+            pw.on_exit();
+            return RenderResult::Exit;
+        }
+        pw.player.handle_input(&pw.controls.actions);
         if pw.render(&window, self.renderer.as_mut().unwrap()) == RenderResult::Exit {
+            pw.on_exit();
             return RenderResult::Exit;
         }
         input.update();
@@ -115,7 +176,8 @@ impl Game for VoxGame {
     }
     fn on_init(&mut self, window: &Window) -> InitResult {
         let renderer = Renderer::new(&window);
-        self.personal_world = Some(PersonalWorld::new(window, &renderer));
+        let level = self.level.take().unwrap();
+        self.personal_world = Some(PersonalWorld::new(window, &renderer, level, &self.save_dir));
         self.renderer = Some(renderer);
         return InitResult::Continue;
     }
@@ -2,13 +2,22 @@
 
 use crate::game::VoxGame;
 use crate::logger::setup_logger;
-use std::time::Instant;
-use vox_core::positions::{ChunkPos, LocalBlockPos, LocalChunkPos};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use vox_core::positions::{ChunkPos, GlobalBlockPos, LocalBlockPos, LocalChunkPos};
 use vox_core::utils::coord_to_array_indice;
+use vox_io::io::compression::{Codec, CompressionStats};
+use vox_io::io::error::VoxIoError;
+use vox_io::io::save_format::{quarantine_file, recompress_file, QUARANTINE_DIR};
 use vox_world::blocks::block::{get_blockid, BlockId};
 use vox_world::blocks::block_type::BlockType;
+use vox_world::structures::schematic::{export_schematic, paste_schematic, BlockNameMapping, BLOCK_MAPPING_FILE};
+use vox_world::world::level_data::LevelData;
+use vox_world::world::small_world::SmallWorld;
 use vox_world::world_gen::basic::ChunkGenerator;
 use vox_world::world_gen::chunk::{Chunk, OldChunk};
+use vox_world::world_gen::meta_chunk::{MetaChunk, SAVE_DIR};
+use vox_world::world_gen::preview::WorldPreview;
+use vox_world::world_gen::world_preset::WorldPreset;
 
 mod game;
 mod logger;
@@ -18,6 +27,97 @@ mod ui;
 fn main() {
     setup_logger().unwrap();
 
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(|a| a.as_str()) == Some("play") {
+        // vox play [preset] [world], the preset is a built in name or a file in the presets directory
+        // and is only used when the world is created, an existing world keeps its own preset
+        let preset_name = args.get(2).map(|a| a.as_str()).unwrap_or("default");
+        let save_dir = format!("{}/{}", SAVE_DIR, args.get(3).map(|a| a.as_str()).unwrap_or("world"));
+        let level = match LevelData::load(&save_dir) {
+            Ok(level) => {
+                println!("resuming {} with seed {} and preset {}", save_dir, level.seed, level.preset.name);
+                level
+            }
+            Err(VoxIoError::NotFound(_)) => {
+                let preset = WorldPreset::from_name(preset_name);
+                if let Err(reason) = preset.validate() {
+                    println!("could not create {} with preset {}, {}", save_dir, preset_name, reason);
+                    return;
+                }
+                let seed = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as u32;
+                println!("creating {} with seed {} and preset {}", save_dir, seed, preset_name);
+                LevelData::new(seed, preset)
+            }
+            // starting over would regenerate the world with another seed and throw away its chunks
+            Err(e) => {
+                println!("could not open {}, {}", save_dir, e);
+                return;
+            }
+        };
+        VoxGame::new(level, &save_dir).run();
+        return;
+    }
+    if args.get(1).map(|a| a.as_str()) == Some("preview") {
+        // vox preview [seed] [preset] [min x] [min z] [size] [output directory], no window is opened
+        let seed = args.get(2).and_then(|a| a.parse().ok()).unwrap_or(0);
+        let preset = WorldPreset::from_name(args.get(3).map(|a| a.as_str()).unwrap_or("default"));
+        if let Err(reason) = preset.validate() {
+            println!("could not use preset {}, {}", preset.name, reason);
+            return;
+        }
+        let min_x = args.get(4).and_then(|a| a.parse().ok()).unwrap_or(0);
+        let min_z = args.get(5).and_then(|a| a.parse().ok()).unwrap_or(0);
+        let size = args.get(6).and_then(|a| a.parse().ok()).unwrap_or(256);
+        let directory = args.get(7).map(|a| a.as_str()).unwrap_or("preview");
+        let generator = ChunkGenerator::from_preset(seed, &preset);
+        let timer = Instant::now();
+        let preview = WorldPreview::generate(&generator, min_x, min_z, size, size);
+        println!(
+            "generated {}x{} preview of preset {} with seed {} in {} seconds",
+            size,
+            size,
+            preset.name,
+            seed,
+            timer.elapsed().as_secs_f32()
+        );
+        for (stage, time) in generator.take_timings() {
+            println!("    {}: {} seconds", stage, time);
+        }
+        if let Err(e) = preview.write_pngs(directory) {
+            println!("could not write preview: {}", e);
+        }
+        return;
+    }
+    if args.get(1).map(|a| a.as_str()) == Some("recompress") {
+        // vox recompress [codec] [world directory], rewrites every save file with the codec
+        let codec_name = args.get(2).map(|a| a.as_str()).unwrap_or("zstd");
+        let codec = match Codec::from_name(codec_name) {
+            Some(c) => c,
+            None => {
+                println!("unknown codec {}, expected none, lz4 or zstd", codec_name);
+                return;
+            }
+        };
+        let directory = args.get(3).map(|a| a.as_str()).unwrap_or(SAVE_DIR);
+        recompress_world(directory, codec);
+        return;
+    }
+    if args.get(1).map(|a| a.as_str()) == Some("verify") {
+        // vox verify [world directory] [quarantine], checks every save file and optionally moves
+        // the broken ones out of the way so they get regenerated
+        let directory = args.get(2).map(|a| a.as_str()).unwrap_or(SAVE_DIR);
+        let quarantine = args.get(3).map(|a| a.as_str()) == Some("quarantine");
+        verify_world(directory, quarantine);
+        return;
+    }
+    if args.get(1).map(|a| a.as_str()) == Some("schematic") {
+        // vox schematic paste [file] [x] [y] [z] [world]
+        // vox schematic export [file] [x1] [y1] [z1] [x2] [y2] [z2] [version] [world]
+        // works on an existing world, pasted blocks are saved into it right away
+        schematic_command(&args[2..]);
+        return;
+    }
+
     let sizes = 64;
     let g = ChunkGenerator::new(0);
     let timer = Instant::now();
@@ -113,3 +213,173 @@ fn main() {
         time
     );
 }
+
+fn parse_block_pos(args: &[String], start: usize) -> Option<GlobalBlockPos> {
+    let coord = |i: usize| args.get(start + i).and_then(|a| a.parse().ok());
+    return Some(GlobalBlockPos {
+        x: coord(0)?,
+        y: coord(1)?,
+        z: coord(2)?,
+    });
+}
+
+fn schematic_command(args: &[String]) {
+    let usage = "usage: vox schematic paste [file] [x] [y] [z] [world] \
+                 or vox schematic export [file] [x1] [y1] [z1] [x2] [y2] [z2] [version] [world]";
+    let (mode, filename) = match (args.get(0), args.get(1)) {
+        (Some(mode), Some(filename)) => (mode.as_str(), filename.as_str()),
+        _ => {
+            println!("{}", usage);
+            return;
+        }
+    };
+    let world_arg = if mode == "paste" { 5 } else { 9 };
+    let save_dir = format!("{}/{}", SAVE_DIR, args.get(world_arg).map(|a| a.as_str()).unwrap_or("world"));
+    let level = match LevelData::load(&save_dir) {
+        Ok(level) => level,
+        Err(e) => {
+            println!("could not open {}, {}", save_dir, e);
+            return;
+        }
+    };
+    let generator = ChunkGenerator::from_preset(level.seed, &level.preset);
+    let mut world = SmallWorld::from_level(&level, &save_dir);
+    let mapping = BlockNameMapping::load_or_default(BLOCK_MAPPING_FILE);
+    match (mode, parse_block_pos(args, 2), parse_block_pos(args, 5)) {
+        ("paste", Some(pos), _) => {
+            if let Err(e) = paste_schematic(&mut world, &generator, filename, &pos, &mapping) {
+                println!("could not paste {}", e);
+                return;
+            }
+            let mut saved = 0;
+            for (chunk_pos, raw) in world.take_modified_chunks() {
+                match MetaChunk::save_serialized(&save_dir, &chunk_pos, &raw) {
+                    Ok(_) => saved += 1,
+                    Err(e) => println!("could not save metachunk {:?}, {}", chunk_pos, e),
+                }
+            }
+            println!("pasted {} at {:?}, saved {} metachunks to {}", filename, pos, saved, save_dir);
+        }
+        ("export", Some(min), Some(max)) => {
+            let version = args.get(8).and_then(|a| a.parse().ok()).unwrap_or(3);
+            world.load_area(&generator, &min, &max);
+            match export_schematic(&world, &min, &max, filename, &mapping, version) {
+                Ok(_) => println!("exported {:?} to {:?} of {} to {}", min, max, save_dir, filename),
+                Err(e) => println!("could not export {}", e),
+            }
+        }
+        _ => println!("{}", usage),
+    }
+}
+
+fn save_files(directory: &str, files: &mut Vec<String>) {
+    let entries = match std::fs::read_dir(directory) {
+        Ok(e) => e,
+        Err(e) => {
+            println!("could not read {}: {}", directory, e);
+            return;
+        }
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            if path.file_name().map_or(false, |n| n == QUARANTINE_DIR) {
+                continue;
+            }
+            save_files(path.to_str().unwrap(), files);
+        } else if path.extension().map_or(false, |e| e == "metachunk") {
+            files.push(path.to_str().unwrap().to_string());
+        }
+    }
+}
+
+fn recompress_world(directory: &str, codec: Codec) {
+    let mut files = Vec::new();
+    save_files(directory, &mut files);
+    files.sort();
+    let mut before_total = CompressionStats::new(Codec::None);
+    let mut after_total = CompressionStats::new(codec);
+    let mut failed = 0;
+    for filename in files.iter() {
+        match recompress_file::<MetaChunk>(filename, codec) {
+            Ok((before, after)) => {
+                println!(
+                    "{}: {} {} bytes -> {} {} bytes, decompressed in {} sec, compressed in {} sec",
+                    filename,
+                    before.codec.name(),
+                    before.compressed_size,
+                    after.codec.name(),
+                    after.compressed_size,
+                    before.time,
+                    after.time
+                );
+                before_total.add(&before);
+                after_total.add(&after);
+            }
+            Err(e) => {
+                println!("could not recompress {}", e);
+                failed += 1;
+            }
+        }
+    }
+    println!(
+        "recompressed {} files in {} with {}, {} failed",
+        files.len() - failed,
+        directory,
+        codec.name(),
+        failed
+    );
+    println!(
+        "    before: {} bytes stored for {} bytes of data ({:.2}x), {} sec decompressing",
+        before_total.compressed_size,
+        before_total.raw_size,
+        before_total.ratio(),
+        before_total.time
+    );
+    println!(
+        "    after: {} bytes stored for {} bytes of data ({:.2}x), {} sec compressing",
+        after_total.compressed_size,
+        after_total.raw_size,
+        after_total.ratio(),
+        after_total.time
+    );
+}
+
+fn verify_world(directory: &str, quarantine: bool) {
+    let mut files = Vec::new();
+    save_files(directory, &mut files);
+    files.sort();
+    let mut corrupt = 0;
+    let mut mismatched = 0;
+    let mut unreadable = 0;
+    for filename in files.iter() {
+        let error = match MetaChunk::load_file(filename) {
+            Ok(_) => continue,
+            Err(e) => e,
+        };
+        println!("{}", error);
+        match error {
+            VoxIoError::Corrupt(..) => corrupt += 1,
+            VoxIoError::VersionMismatch { .. } => mismatched += 1,
+            VoxIoError::NotFound(_) | VoxIoError::Io(..) => {
+                unreadable += 1;
+                continue;
+            }
+        }
+        if quarantine {
+            match quarantine_file(filename) {
+                Ok(target) => println!("    moved to {}", target),
+                Err(e) => println!("    could not quarantine: {}", e),
+            }
+        }
+    }
+    println!(
+        "verified {} files in {}: {} ok, {} corrupt, {} with an unknown version, {} unreadable",
+        files.len(),
+        directory,
+        files.len() - corrupt - mismatched - unreadable,
+        corrupt,
+        mismatched,
+        unreadable
+    );
+}
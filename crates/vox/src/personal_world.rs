@@ -3,19 +3,22 @@ use cgmath::{InnerSpace, Vector3};
 use rayon::prelude::ParallelSliceMut;
 use std::cmp::Ordering;
 use std::collections::{BinaryHeap, HashMap, HashSet};
-use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use std::time::Instant;
 use vox_core::constants::{
-    CHUNKSIZE, METACHUNKSIZE, METACHUNK_GEN_RANGE, METACHUNK_UNLOAD_RADIUS, SEED,
+    CHUNKSIZE, METACHUNKSIZE, METACHUNK_GEN_RANGE, METACHUNK_UNLOAD_RADIUS,
 };
-use vox_core::positions::{ChunkPos, MetaChunkPos};
+use vox_core::positions::{ChunkPos, GlobalBlockPos, MetaChunkPos};
 use vox_render::renderer::renderer::Renderer;
 use vox_render::renderer::renderpassable::RenderPassable;
 use vox_render::renderer::wgpu::WgpuState;
 use vox_render::renderer::wgpu_pipeline::WgpuPipeline;
 use vox_world::chunk_render_data::ChunkRenderData;
+use vox_world::controls::{Controls, ControlsConfig, CONTROLS_FILE};
 use vox_world::player::Player;
+use vox_world::world::chunk_io_thread::{ChunkIoResult, ChunkIoThread};
+use vox_world::world::level_data::{LevelData, LEVEL_SAVE_INTERVAL};
 use vox_world::world::small_world::SmallWorld;
-use vox_world::world_gen::chunk_gen_thread::ChunkGenThread;
+use vox_world::world_gen::chunk_gen_pool::ChunkGenPool;
 use vox_world::world_gen::meta_chunk::MetaChunk;
 use winit::window::Window;
 use winit_window_control::input::input::Input;
@@ -25,38 +28,80 @@ pub struct PersonalWorld {
     pub world: SmallWorld,
     pub chunk_render_data: HashMap<ChunkPos, ChunkRenderData>,
     pub player: Player,
-    pub chunk_gen_thread: ChunkGenThread,
+    pub controls: Controls,
+    pub chunk_gen_pool: ChunkGenPool,
+    pub chunk_io: ChunkIoThread,
     pub loading_chunks: HashSet<MetaChunkPos>,
     pub reload_vertex_load_order: bool,
     pub to_generate: Vec<(f32, ChunkPos)>,
     pub ui: UiRenderer,
+    pub level: LevelData,
+    pub last_level_save: Instant,
+    // chunks whose blocks were edited, their meshes are rebuilt before the next frame
+    pub dirty_chunks: HashSet<ChunkPos>,
+    // set by on_exit, dropping the world without it saves everything first
+    pub exited: bool,
 }
 
 impl PersonalWorld {
-    pub fn new(window: &Window, renderer: &Renderer) -> PersonalWorld {
+    pub fn new(window: &Window, renderer: &Renderer, level: LevelData, save_dir: &str) -> PersonalWorld {
         let ui_renderer = UiRenderer::new(window, &renderer);
+        let mut player = Player::new();
+        level.apply_to_player(&mut player);
         PersonalWorld {
-            world: SmallWorld::new(
-                SystemTime::now()
-                    .duration_since(UNIX_EPOCH)
-                    .unwrap()
-                    .as_secs() as u32,
-            ),
+            chunk_gen_pool: ChunkGenPool::new(level.preset.clone(), ChunkGenPool::default_worker_count()),
+            chunk_io: ChunkIoThread::new(save_dir),
+            world: SmallWorld::from_level(&level, save_dir),
             chunk_render_data: HashMap::new(),
-            player: Player::new(),
-            chunk_gen_thread: ChunkGenThread::new(),
+            player,
+            controls: Controls::new(ControlsConfig::load_or_default(CONTROLS_FILE)),
             loading_chunks: HashSet::new(),
             reload_vertex_load_order: false,
             to_generate: Vec::new(),
             ui: ui_renderer,
+            level,
+            last_level_save: Instant::now(),
+            dirty_chunks: HashSet::new(),
+            exited: false,
+        }
+    }
+    pub fn save_level(&mut self) {
+        self.level.update(&self.world, &self.player);
+        match self.level.save(&self.world.save_dir) {
+            Ok(_) => println!("saved level data of {}", self.world.save_dir),
+            Err(e) => println!("could not save level data, {}", e),
+        }
+        self.last_level_save = Instant::now();
+    }
+    pub fn save_modified_chunks(&mut self) {
+        self.chunk_io.save_modified_chunks(&mut self.world);
+    }
+    // writes everything that would otherwise be lost when the game closes and waits for it
+    pub fn on_exit(&mut self) {
+        self.save_level();
+        self.save_modified_chunks();
+        self.chunk_io.flush();
+        self.handle_io_results();
+        match self.chunk_io.failed_saves() {
+            0 => println!("saved {}", self.world.save_dir),
+            failed => println!("saved {}, the changes to {} metachunks are lost", self.world.save_dir, failed),
         }
+        self.exited = true;
     }
     pub fn update(&mut self) {
         self.world.update();
     }
     pub fn on_game_tick(&mut self, dt: f32) {
         self.player.update(&dt, &self.world);
+        if let Some(pos) = self.player.handle_edits(&dt, &mut self.world) {
+            self.mark_block_dirty(&pos);
+        }
         self.update();
+        if self.last_level_save.elapsed().as_secs_f32() > LEVEL_SAVE_INTERVAL {
+            self.save_level();
+            self.save_modified_chunks();
+        }
+        self.handle_io_results();
         self.load_generated_chunks();
         self.to_generate = self.vertex_buffers_to_generate();
         if self.player.generated_chunks_for != self.player.position.get_chunk()
@@ -109,11 +154,8 @@ impl PersonalWorld {
         if self.chunk_render_data.contains_key(&pos) {
             return (false, 0.0);
         }
-        let view_dir = Vector3::new(
-            self.player.direction.x,
-            self.player.direction.y,
-            self.player.direction.z,
-        );
+        let direction = self.player.direction();
+        let view_dir = Vector3::new(direction[0], direction[1], direction[2]);
         let viewer_pos = Vector3::new(
             self.player.position.x,
             self.player.position.y,
@@ -143,15 +185,21 @@ impl PersonalWorld {
             return;
         }
         self.loading_chunks.insert(pos.clone());
-        let chunk_request_result = self.chunk_gen_thread.request(pos, SEED);
-        match chunk_request_result {
-            Ok(_) => (),
-            Err(e) => println!("error while trying to load A chunk: {}", e),
-        }
+        // goes to the generator when there is no save
+        self.chunk_io.load(pos, self.world.world_seed);
     }
     pub fn on_player_moved_chunks(&mut self) {
+        let cancelled = self.chunk_gen_pool.update_center(
+            self.player.position.get_meta_chunk(),
+            METACHUNK_UNLOAD_RADIUS as i32,
+        );
+        for pos in cancelled {
+            self.loading_chunks.remove(&pos);
+        }
         self.check_chunks_to_generate();
-        self.world.filter_chunks(&self.player);
+        for (pos, raw) in self.world.filter_chunks(&self.player) {
+            self.chunk_io.save(pos, raw);
+        }
         let player = &self.player;
         self.chunk_render_data
             .retain(|pos, _| MetaChunk::retain_meta_chunk(player, pos.get_meta_chunk_pos()));
@@ -183,6 +231,33 @@ impl PersonalWorld {
             self.load_chunk(to_load.pop().unwrap().1);
         }
     }
+    // a block on the side of a chunk also changes the faces drawn by the neighbouring chunk
+    pub fn mark_block_dirty(&mut self, pos: &GlobalBlockPos) {
+        let chunk = pos.get_chunk_pos();
+        let local = pos.get_local_pos();
+        self.dirty_chunks.insert(chunk.clone());
+        let last = CHUNKSIZE as i32 - 1;
+        for (local, axis) in [(local.x, 0), (local.y, 1), (local.z, 2)] {
+            let mut offset = [0; 3];
+            if local == 0 {
+                offset[axis] = -1;
+            } else if local == last {
+                offset[axis] = 1;
+            } else {
+                continue;
+            }
+            self.dirty_chunks.insert(chunk.get_diff(offset[0], offset[1], offset[2]));
+        }
+    }
+    // only chunks that already have a mesh are rebuilt, the others get one when they are generated
+    pub fn rebuild_dirty_chunks(&mut self, renderer: &Renderer) {
+        for pos in self.dirty_chunks.drain() {
+            if self.chunk_render_data.contains_key(&pos) {
+                let data = ChunkRenderData::new(&self.world, &pos, &renderer.wgpu.device);
+                self.chunk_render_data.insert(pos, data);
+            }
+        }
+    }
     pub fn check_vertices_to_generate(&mut self, renderer: &Renderer) -> i32 {
         if self.to_generate.is_empty() {
             return 0;
@@ -206,8 +281,41 @@ impl PersonalWorld {
         );*/
         return (starting_size - self.to_generate.len()) as i32;
     }
+    pub fn handle_io_results(&mut self) {
+        while let Ok(result) = self.chunk_io.get() {
+            match result {
+                ChunkIoResult::Loaded(chunk, pos) => {
+                    self.loading_chunks.remove(&pos);
+                    self.world.add_chunk(pos, chunk);
+                    self.reload_vertex_load_order = true;
+                }
+                ChunkIoResult::Missing(pos, seed) => {
+                    if PersonalWorld::meta_chunk_should_be_loaded(&self.player, &pos) {
+                        self.chunk_gen_pool.request(pos, seed);
+                    } else {
+                        self.loading_chunks.remove(&pos);
+                    }
+                }
+                ChunkIoResult::Saved(pos, stats) => {
+                    println!(
+                        "saved chunk {:?}, {} bytes compressed to {} with {} ({:.2}x) in {} sec",
+                        pos,
+                        stats.raw_size,
+                        stats.compressed_size,
+                        stats.codec.name(),
+                        stats.ratio(),
+                        stats.time
+                    );
+                    self.world.save_stats.add(&stats);
+                }
+                ChunkIoResult::SaveFailed(pos, e) => {
+                    println!("could not save chunk {:?}, {}, retrying with the next save", pos, e)
+                }
+            }
+        }
+    }
     pub fn load_generated_chunks(&mut self) {
-        let message = self.chunk_gen_thread.get();
+        let message = self.chunk_gen_pool.get();
         match message {
             Ok((chunk, pos)) => {
                 self.loading_chunks.remove(&pos);
@@ -221,6 +329,7 @@ impl PersonalWorld {
         self.ui.update_input(input);
     }
     pub fn render(&mut self, window: &Window, renderer: &mut Renderer) -> RenderResult {
+        self.rebuild_dirty_chunks(renderer);
         let main_pipeline = renderer.pipelines.get_mut("main").unwrap();
         main_pipeline.uniforms.update_view_proj(
             [
@@ -229,9 +338,12 @@ impl PersonalWorld {
                 self.player.position.z,
             ],
             self.player.get_view_matrix(),
-            (renderer.wgpu.size.width, renderer.wgpu.size.height),
+            self.player
+                .camera
+                .projection_matrix(renderer.wgpu.size.width as f32 / renderer.wgpu.size.height.max(1) as f32),
             self.world.time,
         );
+        main_pipeline.uniforms.set_underwater(self.player.fluid.eyes_underwater);
         main_pipeline.set_uniform_buffer(&renderer.wgpu.queue, main_pipeline.uniforms);
         match renderer.do_render_pass(window, self) {
             Ok(_) => {}
@@ -282,7 +394,8 @@ impl RenderPassable for PersonalWorld {
                 depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
                     view: &wgpu_state.depth_texture.view,
                     depth_ops: Some(wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(1.0),
+                        // depth is reversed, 0 is the far plane
+                        load: wgpu::LoadOp::Clear(0.0),
                         store: true,
                     }),
                     stencil_ops: None,
@@ -334,3 +447,12 @@ impl RenderPassable for PersonalWorld {
         }
     }
 }
+
+impl Drop for PersonalWorld {
+    // closing the window drops the game without going through the exit action
+    fn drop(&mut self) {
+        if !self.exited {
+            self.on_exit();
+        }
+    }
+}
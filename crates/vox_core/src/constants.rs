@@ -13,12 +13,12 @@ pub const COLORS: [[f32; 4]; 16] = [
     [255.0, 0.0, 0.0, 255.0],
     [255.0, 0.0, 255.0, 0.0],
     [0.0, 128.0, 0.0, 254.99],
-    [0.0; 4],
-    [0.0; 4],
-    [0.0; 4],
-    [0.0; 4],
-    [0.0; 4],
-    [0.0; 4],
+    [40.0, 40.0, 40.0, 255.0],
+    [200.0, 160.0, 130.0, 255.0],
+    [255.0, 215.0, 0.0, 255.0],
+    [80.0, 230.0, 230.0, 255.0],
+    [110.0, 75.0, 40.0, 255.0],
+    [225.0, 225.0, 215.0, 255.0],
     [0.0; 4],
     [0.0; 4],
     [0.0; 4],
@@ -171,18 +171,14 @@ impl MetaChunkPos {
 impl ObjectPos {
     pub fn get_block(&self) -> GlobalBlockPos {
         GlobalBlockPos {
-            x: self.x as i32,
-            y: self.y as i32,
-            z: self.z as i32,
+            x: self.x.floor() as i32,
+            y: self.y.floor() as i32,
+            z: self.z.floor() as i32,
         }
     }
 
     pub fn get_chunk(&self) -> ChunkPos {
-        ChunkPos {
-            x: self.x as i32 / CHUNKSIZE as i32,
-            y: self.y as i32 / CHUNKSIZE as i32,
-            z: self.z as i32 / CHUNKSIZE as i32,
-        }
+        self.get_block().get_chunk_pos()
     }
     pub fn get_distance(&self, pos: &ObjectPos) -> f32 {
         ((self.x - pos.x).pow(2) as f32
@@ -191,10 +187,7 @@ impl ObjectPos {
             .sqrt()
     }
     pub fn get_meta_chunk(&self) -> MetaChunkPos {
-        MetaChunkPos {
-            x: self.x as i32 / (CHUNKSIZE as i32 * METACHUNKSIZE as i32),
-            z: self.z as i32 / (CHUNKSIZE as i32 * METACHUNKSIZE as i32),
-        }
+        self.get_block().get_meta_chunk_pos()
     }
     pub fn get_diff(&self, x_diff: f32, y_diff: f32, z_diff: f32) -> ObjectPos {
         ObjectPos {
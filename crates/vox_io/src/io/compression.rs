@@ -0,0 +1,97 @@
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+
+// how the payload of a save file is compressed, stored as a single byte in front of the payload
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Codec {
+    None,
+    // fast, used for chunks that are saved while playing
+    Lz4,
+    // smaller but slower, meant for worlds that are archived or shared
+    Zstd,
+}
+
+const ZSTD_LEVEL: i32 = 3;
+
+impl Codec {
+    pub const ALL: [Codec; 3] = [Codec::None, Codec::Lz4, Codec::Zstd];
+
+    pub fn id(&self) -> u8 {
+        match self {
+            Codec::None => 0,
+            Codec::Lz4 => 1,
+            Codec::Zstd => 2,
+        }
+    }
+    pub fn from_id(id: u8) -> Option<Codec> {
+        Codec::ALL.iter().copied().find(|c| c.id() == id)
+    }
+    pub fn name(&self) -> &'static str {
+        match self {
+            Codec::None => "none",
+            Codec::Lz4 => "lz4",
+            Codec::Zstd => "zstd",
+        }
+    }
+    pub fn from_name(name: &str) -> Option<Codec> {
+        Codec::ALL.iter().copied().find(|c| c.name() == name)
+    }
+    pub fn compress(&self, data: &[u8]) -> Vec<u8> {
+        match self {
+            Codec::None => data.to_vec(),
+            Codec::Lz4 => lz4_flex::compress_prepend_size(data),
+            // compressing from memory into memory can't fail
+            Codec::Zstd => zstd::encode_all(data, ZSTD_LEVEL).unwrap(),
+        }
+    }
+    pub fn decompress(&self, data: &[u8]) -> Result<Vec<u8>, String> {
+        match self {
+            Codec::None => Ok(data.to_vec()),
+            Codec::Lz4 => lz4_flex::decompress_size_prepended(data).map_err(|e| e.to_string()),
+            Codec::Zstd => zstd::decode_all(data).map_err(|e| e.to_string()),
+        }
+    }
+}
+
+// sizes and time spent on compressing or decompressing, can be summed over many files
+#[derive(Debug, Clone, Copy)]
+pub struct CompressionStats {
+    pub codec: Codec,
+    pub raw_size: usize,
+    pub compressed_size: usize,
+    pub time: f32,
+}
+
+impl CompressionStats {
+    pub fn new(codec: Codec) -> CompressionStats {
+        CompressionStats {
+            codec,
+            raw_size: 0,
+            compressed_size: 0,
+            time: 0.0,
+        }
+    }
+    pub fn ratio(&self) -> f32 {
+        if self.compressed_size == 0 {
+            return 1.0;
+        }
+        return self.raw_size as f32 / self.compressed_size as f32;
+    }
+    pub fn add(&mut self, other: &CompressionStats) {
+        self.raw_size += other.raw_size;
+        self.compressed_size += other.compressed_size;
+        self.time += other.time;
+    }
+}
+
+pub fn compress_timed(codec: Codec, data: &[u8]) -> (Vec<u8>, CompressionStats) {
+    let timer = Instant::now();
+    let compressed = codec.compress(data);
+    let stats = CompressionStats {
+        codec,
+        raw_size: data.len(),
+        compressed_size: compressed.len(),
+        time: timer.elapsed().as_secs_f32(),
+    };
+    return (compressed, stats);
+}
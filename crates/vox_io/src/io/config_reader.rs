@@ -0,0 +1,10 @@
+use crate::io::error::VoxIoError;
+use serde::Deserialize;
+use std::fs::File;
+use std::io::BufReader;
+
+pub fn read_config_from_file<T: for<'de> Deserialize<'de>>(filename: &str) -> Result<T, VoxIoError> {
+    let f = File::open(filename).map_err(|e| VoxIoError::from_io(filename, e))?;
+    let reader = BufReader::new(f);
+    return ron::de::from_reader(reader).map_err(|e| VoxIoError::Corrupt(filename.to_string(), e.to_string()));
+}
@@ -0,0 +1,69 @@
+use std::fmt;
+use std::io::ErrorKind;
+
+// everything that can go wrong while reading or writing a file, callers decide per case
+// whether to regenerate, fall back to a default or give up
+#[derive(Debug)]
+pub enum VoxIoError {
+    // the file does not exist
+    NotFound(String),
+    // the file exists but its contents can't be decoded
+    Corrupt(String, String),
+    // the file was written by a format version this build can't read
+    VersionMismatch { filename: String, found: u32, expected: u32 },
+    // the file could not be opened, read or written, e.g. because of permissions or a full disk
+    Io(String, std::io::Error),
+}
+
+impl VoxIoError {
+    pub fn from_io(filename: &str, e: std::io::Error) -> VoxIoError {
+        match e.kind() {
+            ErrorKind::NotFound => VoxIoError::NotFound(filename.to_string()),
+            ErrorKind::InvalidData | ErrorKind::UnexpectedEof => VoxIoError::Corrupt(filename.to_string(), e.to_string()),
+            _ => VoxIoError::Io(filename.to_string(), e),
+        }
+    }
+    pub fn from_bincode(filename: &str, e: bincode::Error) -> VoxIoError {
+        match *e {
+            bincode::ErrorKind::Io(e) => VoxIoError::from_io(filename, e),
+            e => VoxIoError::Corrupt(filename.to_string(), e.to_string()),
+        }
+    }
+    // parsers of data that is already in memory can only fail on malformed contents, even when the
+    // error is an end of file or another io kind
+    pub fn from_parser(filename: &str, e: std::io::Error) -> VoxIoError {
+        VoxIoError::Corrupt(filename.to_string(), e.to_string())
+    }
+    pub fn filename(&self) -> &str {
+        match self {
+            VoxIoError::NotFound(filename) => filename,
+            VoxIoError::Corrupt(filename, _) => filename,
+            VoxIoError::VersionMismatch { filename, .. } => filename,
+            VoxIoError::Io(filename, _) => filename,
+        }
+    }
+}
+
+impl fmt::Display for VoxIoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VoxIoError::NotFound(filename) => write!(f, "{} does not exist", filename),
+            VoxIoError::Corrupt(filename, reason) => write!(f, "{} is corrupt: {}", filename, reason),
+            VoxIoError::VersionMismatch { filename, found, expected } => write!(
+                f,
+                "{} has format version {}, expected version {}",
+                filename, found, expected
+            ),
+            VoxIoError::Io(filename, e) => write!(f, "io error on {}: {}", filename, e),
+        }
+    }
+}
+
+impl std::error::Error for VoxIoError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            VoxIoError::Io(_, e) => Some(e),
+            _ => None,
+        }
+    }
+}
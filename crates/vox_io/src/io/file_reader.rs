@@ -1,13 +1,26 @@
+use crate::io::error::VoxIoError;
+use crate::io::save_format::{decode_file, is_bincode, Migrations};
 use serde::Deserialize;
 use std::fs::File;
-use std::io::BufReader;
+use std::io::Read;
 
-pub fn read_meta_chunk_from_file<T: for<'de> Deserialize<'de>>(filename: &str) -> Option<T> {
-    let f = File::open(filename);
-    if f.is_ok() {
-        let reader = BufReader::new(f.unwrap());
-        let obj = bincode::deserialize_from(reader).unwrap();
-        return Some(obj);
-    }
-    return None;
+pub fn read_meta_chunk_from_file<T: for<'de> Deserialize<'de>>(filename: &str) -> Result<T, VoxIoError> {
+    return read_from_file_with_migrations(filename, &Migrations::default());
+}
+
+// older files are upgraded in memory, the file itself is only rewritten on the next save
+pub fn read_from_file_with_migrations<T: for<'de> Deserialize<'de>>(
+    filename: &str,
+    migrations: &Migrations,
+) -> Result<T, VoxIoError> {
+    let mut f = File::open(filename).map_err(|e| VoxIoError::from_io(filename, e))?;
+    let mut data = Vec::new();
+    f.read_to_end(&mut data).map_err(|e| VoxIoError::from_io(filename, e))?;
+    let (raw, _) = decode_file(filename, &data, migrations, is_bincode::<T>)?;
+    return deserialize_serialized(filename, &raw);
+}
+
+// the counterpart of serialize_for_file, for data that has not been written yet
+pub fn deserialize_serialized<T: for<'de> Deserialize<'de>>(filename: &str, raw: &[u8]) -> Result<T, VoxIoError> {
+    return bincode::deserialize(raw).map_err(|e| VoxIoError::from_bincode(filename, e));
 }
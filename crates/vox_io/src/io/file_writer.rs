@@ -1,9 +1,80 @@
+use crate::io::compression::{Codec, CompressionStats};
+use crate::io::error::VoxIoError;
+use crate::io::save_format::encode_file;
 use serde::Serialize;
 use std::fs::File;
-use std::io::BufWriter;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
 
-pub fn write_to_file<T: Serialize>(filename: &str, obj: &T) {
-    let file = File::create(filename).unwrap();
-    let writer = BufWriter::new(file);
-    bincode::serialize_into(writer, obj).unwrap();
+pub fn write_to_file<T: Serialize>(filename: &str, obj: &T) -> Result<(), VoxIoError> {
+    write_to_file_with_codec(filename, obj, Codec::None)?;
+    return Ok(());
+}
+
+pub fn write_to_file_with_codec<T: Serialize>(
+    filename: &str,
+    obj: &T,
+    codec: Codec,
+) -> Result<CompressionStats, VoxIoError> {
+    let raw = serialize_for_file(filename, obj)?;
+    return write_serialized_to_file(filename, &raw, codec);
+}
+
+// serializing and writing are split up so the slow part, compressing and writing, can be done on
+// another thread than the one that owns the object
+pub fn serialize_for_file<T: Serialize>(filename: &str, obj: &T) -> Result<Vec<u8>, VoxIoError> {
+    return bincode::serialize(obj).map_err(|e| VoxIoError::from_bincode(filename, e));
+}
+
+pub fn write_serialized_to_file(filename: &str, raw: &[u8], codec: Codec) -> Result<CompressionStats, VoxIoError> {
+    let (data, stats) = encode_file(codec, raw);
+    write_bytes_to_file(filename, &data)?;
+    return Ok(stats);
+}
+
+// every write gets its own temporary file, so two writes of the same file don't write into each other
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+// the data is written to a temporary file next to the destination which then replaces it, so a
+// crash while writing leaves either the old or the new file and never a partial one
+pub fn write_bytes_to_file(filename: &str, data: &[u8]) -> Result<(), VoxIoError> {
+    let temp_filename = format!(
+        "{}.{}.{}.tmp",
+        filename,
+        std::process::id(),
+        TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+    );
+    if let Err(e) = write_temp_file(&temp_filename, data) {
+        let _ = std::fs::remove_file(&temp_filename);
+        return Err(VoxIoError::Io(temp_filename, e));
+    }
+    if let Err(e) = std::fs::rename(&temp_filename, filename) {
+        let _ = std::fs::remove_file(&temp_filename);
+        return Err(VoxIoError::Io(filename.to_string(), e));
+    }
+    return sync_parent_dir(filename).map_err(|e| VoxIoError::Io(filename.to_string(), e));
+}
+
+fn write_temp_file(temp_filename: &str, data: &[u8]) -> std::io::Result<()> {
+    let mut writer = BufWriter::new(File::create(temp_filename)?);
+    writer.write_all(data)?;
+    let file = writer.into_inner().map_err(|e| e.into_error())?;
+    return file.sync_all();
+}
+
+// the rename is only on disk once the directory holding the file is synced
+#[cfg(unix)]
+fn sync_parent_dir(filename: &str) -> std::io::Result<()> {
+    let directory = match Path::new(filename).parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    return File::open(directory)?.sync_all();
+}
+
+// directories can't be opened as files on other platforms
+#[cfg(not(unix))]
+fn sync_parent_dir(_: &str) -> std::io::Result<()> {
+    return Ok(());
 }
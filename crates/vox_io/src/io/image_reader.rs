@@ -0,0 +1,56 @@
+use crate::io::error::VoxIoError;
+use std::fs::File;
+
+// rgba image with every channel normalised to 0.0..=1.0, independent of the bit depth of the file
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<[f32; 4]>,
+}
+
+impl Image {
+    pub fn get_pixel(&self, x: u32, y: u32) -> [f32; 4] {
+        self.pixels[(x + y * self.width) as usize]
+    }
+}
+
+pub fn read_png_from_file(filename: &str) -> Result<Image, VoxIoError> {
+    let f = File::open(filename).map_err(|e| VoxIoError::from_io(filename, e))?;
+    let mut decoder = png::Decoder::new(f);
+    decoder.set_transformations(png::Transformations::EXPAND);
+    let mut reader = decoder.read_info().map_err(|e| decoding_error(filename, e))?;
+    let mut buffer = vec![0u8; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer).map_err(|e| decoding_error(filename, e))?;
+    let samples: Vec<f32> = match info.bit_depth {
+        png::BitDepth::Sixteen => buffer[..info.buffer_size()]
+            .chunks_exact(2)
+            .map(|s| u16::from_be_bytes([s[0], s[1]]) as f32 / u16::MAX as f32)
+            .collect(),
+        _ => buffer[..info.buffer_size()]
+            .iter()
+            .map(|s| *s as f32 / u8::MAX as f32)
+            .collect(),
+    };
+    let channels = info.color_type.samples();
+    let pixels = samples
+        .chunks_exact(channels)
+        .map(|p| match channels {
+            1 => [p[0], p[0], p[0], 1.0],
+            2 => [p[0], p[0], p[0], p[1]],
+            3 => [p[0], p[1], p[2], 1.0],
+            _ => [p[0], p[1], p[2], p[3]],
+        })
+        .collect();
+    return Ok(Image {
+        width: info.width,
+        height: info.height,
+        pixels,
+    });
+}
+
+fn decoding_error(filename: &str, e: png::DecodingError) -> VoxIoError {
+    match e {
+        png::DecodingError::IoError(e) => VoxIoError::from_io(filename, e),
+        e => VoxIoError::Corrupt(filename.to_string(), e.to_string()),
+    }
+}
@@ -0,0 +1,24 @@
+use crate::io::error::VoxIoError;
+use std::fs::File;
+use std::io::BufWriter;
+
+// pixels are 8 bit rgba, row by row starting at the top left
+pub fn write_png_to_file(filename: &str, width: u32, height: u32, pixels: &[[u8; 4]]) -> Result<(), VoxIoError> {
+    let file = File::create(filename).map_err(|e| VoxIoError::from_io(filename, e))?;
+    let writer = BufWriter::new(file);
+    let mut encoder = png::Encoder::new(writer, width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let data: Vec<u8> = pixels.iter().flatten().copied().collect();
+    let mut png_writer = encoder.write_header().map_err(|e| encoding_error(filename, e))?;
+    png_writer.write_image_data(&data).map_err(|e| encoding_error(filename, e))?;
+    png_writer.finish().map_err(|e| encoding_error(filename, e))?;
+    return Ok(());
+}
+
+fn encoding_error(filename: &str, e: png::EncodingError) -> VoxIoError {
+    match e {
+        png::EncodingError::IoError(e) => VoxIoError::Io(filename.to_string(), e),
+        e => VoxIoError::Corrupt(filename.to_string(), e.to_string()),
+    }
+}
@@ -0,0 +1,355 @@
+use crate::io::error::VoxIoError;
+use std::collections::HashMap;
+use std::convert::TryInto;
+use std::fs::File;
+use std::io::{BufWriter, Error, ErrorKind, Read, Result, Write};
+
+// coordinates in this module are in MagicaVoxel space, where z points up
+
+pub struct VoxModel {
+    pub size: [u32; 3],
+    // x, y, z and palette index, index 0 is never used for a voxel
+    pub voxels: Vec<[u8; 4]>,
+    // position of the minimum corner of the model in the scene
+    pub position: [i32; 3],
+}
+
+pub struct VoxScene {
+    pub models: Vec<VoxModel>,
+    // rgba per palette index, index 0 is empty space
+    pub palette: [[u8; 4]; 256],
+}
+
+enum SceneNode {
+    Transform { child: i32, translation: [i32; 3] },
+    Group { children: Vec<i32> },
+    Shape { models: Vec<i32> },
+}
+
+pub fn read_vox_from_file(filename: &str) -> std::result::Result<VoxScene, VoxIoError> {
+    let mut f = File::open(filename).map_err(|e| VoxIoError::from_io(filename, e))?;
+    let mut data = Vec::new();
+    f.read_to_end(&mut data).map_err(|e| VoxIoError::from_io(filename, e))?;
+    return parse_vox(&data).map_err(|e| VoxIoError::from_parser(filename, e));
+}
+
+pub fn write_vox_to_file(filename: &str, scene: &VoxScene) -> std::result::Result<(), VoxIoError> {
+    let file = File::create(filename).map_err(|e| VoxIoError::from_io(filename, e))?;
+    let mut writer = BufWriter::new(file);
+    writer.write_all(&encode_vox(scene)).map_err(|e| VoxIoError::Io(filename.to_string(), e))?;
+    writer.flush().map_err(|e| VoxIoError::Io(filename.to_string(), e))?;
+    return Ok(());
+}
+
+pub fn parse_vox(data: &[u8]) -> Result<VoxScene> {
+    let mut reader = ByteReader { data, pos: 0 };
+    if reader.take(4)? != b"VOX " {
+        return Err(invalid("not a vox file"));
+    }
+    let _version = reader.i32()?;
+    if reader.take(4)? != b"MAIN" {
+        return Err(invalid("missing MAIN chunk"));
+    }
+    let main_content = reader.len()?;
+    let _main_children = reader.i32()?;
+    reader.take(main_content)?;
+
+    let mut sizes = Vec::new();
+    let mut models = Vec::new();
+    let mut palette = None;
+    let mut nodes = HashMap::new();
+    while reader.pos < data.len() {
+        let id: [u8; 4] = reader.take(4)?.try_into().unwrap();
+        let content_size = reader.len()?;
+        let children_size = reader.len()?;
+        let mut content = ByteReader {
+            data: reader.take(content_size)?,
+            pos: 0,
+        };
+        reader.take(children_size)?;
+        match &id {
+            b"SIZE" => sizes.push([content.len()? as u32, content.len()? as u32, content.len()? as u32]),
+            b"XYZI" => {
+                let count = content.len()?;
+                // every voxel takes 4 bytes, a count beyond that is caught by take below
+                let mut voxels = Vec::with_capacity(count.min(content.data.len() / 4));
+                for _ in 0..count {
+                    voxels.push(content.take(4)?.try_into().unwrap());
+                }
+                models.push(voxels);
+            }
+            b"RGBA" => {
+                let mut colors = [[0u8; 4]; 256];
+                // the file stores the colors of index 1 to 255 followed by an unused entry
+                for i in 1..256 {
+                    colors[i] = content.take(4)?.try_into().unwrap();
+                }
+                palette = Some(colors);
+            }
+            b"nTRN" => {
+                let node_id = content.i32()?;
+                content.dict()?;
+                let child = content.i32()?;
+                let _reserved = content.i32()?;
+                let _layer = content.i32()?;
+                let frames = content.i32()?;
+                let mut translation = [0; 3];
+                if frames > 0 {
+                    if let Some(t) = content.dict()?.get("_t") {
+                        let parts: Vec<i32> = t.split_whitespace().filter_map(|p| p.parse().ok()).collect();
+                        if parts.len() == 3 {
+                            translation = [parts[0], parts[1], parts[2]];
+                        }
+                    }
+                }
+                nodes.insert(node_id, SceneNode::Transform { child, translation });
+            }
+            b"nGRP" => {
+                let node_id = content.i32()?;
+                content.dict()?;
+                let count = content.i32()?;
+                let mut children = Vec::new();
+                for _ in 0..count {
+                    children.push(content.i32()?);
+                }
+                nodes.insert(node_id, SceneNode::Group { children });
+            }
+            b"nSHP" => {
+                let node_id = content.i32()?;
+                content.dict()?;
+                let count = content.i32()?;
+                let mut shape_models = Vec::new();
+                for _ in 0..count {
+                    shape_models.push(content.i32()?);
+                    content.dict()?;
+                }
+                nodes.insert(node_id, SceneNode::Shape { models: shape_models });
+            }
+            _ => {}
+        }
+    }
+    if sizes.len() != models.len() {
+        return Err(invalid("SIZE and XYZI chunks do not match up"));
+    }
+
+    let mut models: Vec<VoxModel> = sizes
+        .into_iter()
+        .zip(models.into_iter())
+        .map(|(size, voxels)| VoxModel {
+            size,
+            voxels,
+            position: [0; 3],
+        })
+        .collect();
+    if nodes.contains_key(&0) {
+        let mut placed = Vec::new();
+        collect_shapes(&nodes, 0, [0; 3], &mut placed, 0);
+        for (model_id, translation) in placed {
+            if let Some(model) = models.get_mut(model_id as usize) {
+                // translations point at the centre of a model
+                model.position = [
+                    translation[0].saturating_sub((model.size[0] / 2) as i32),
+                    translation[1].saturating_sub((model.size[1] / 2) as i32),
+                    translation[2].saturating_sub((model.size[2] / 2) as i32),
+                ];
+            }
+        }
+    }
+    return Ok(VoxScene {
+        models,
+        palette: palette.unwrap_or_else(fallback_palette),
+    });
+}
+
+pub fn encode_vox(scene: &VoxScene) -> Vec<u8> {
+    let mut children = Vec::new();
+    let model_count = scene.models.len() as i32;
+    write_chunk(&mut children, b"PACK", &model_count.to_le_bytes());
+    for model in &scene.models {
+        let mut size = Vec::new();
+        for s in model.size.iter() {
+            size.extend_from_slice(&(*s as i32).to_le_bytes());
+        }
+        write_chunk(&mut children, b"SIZE", &size);
+        let mut voxels = Vec::with_capacity(4 + model.voxels.len() * 4);
+        voxels.extend_from_slice(&(model.voxels.len() as i32).to_le_bytes());
+        for voxel in &model.voxels {
+            voxels.extend_from_slice(voxel);
+        }
+        write_chunk(&mut children, b"XYZI", &voxels);
+    }
+
+    // root transform -> group -> one transform and shape per model
+    let mut root = Vec::new();
+    write_transform_node(&mut root, 0, 1, [0; 3]);
+    write_chunk(&mut children, b"nTRN", &root);
+    let mut group = Vec::new();
+    group.extend_from_slice(&1i32.to_le_bytes());
+    write_dict(&mut group, &[]);
+    group.extend_from_slice(&model_count.to_le_bytes());
+    for i in 0..model_count {
+        group.extend_from_slice(&(2 + i * 2).to_le_bytes());
+    }
+    write_chunk(&mut children, b"nGRP", &group);
+    for (i, model) in scene.models.iter().enumerate() {
+        let i = i as i32;
+        let translation = [
+            model.position[0] + (model.size[0] / 2) as i32,
+            model.position[1] + (model.size[1] / 2) as i32,
+            model.position[2] + (model.size[2] / 2) as i32,
+        ];
+        let mut transform = Vec::new();
+        write_transform_node(&mut transform, 2 + i * 2, 3 + i * 2, translation);
+        write_chunk(&mut children, b"nTRN", &transform);
+        let mut shape = Vec::new();
+        shape.extend_from_slice(&(3 + i * 2).to_le_bytes());
+        write_dict(&mut shape, &[]);
+        shape.extend_from_slice(&1i32.to_le_bytes());
+        shape.extend_from_slice(&i.to_le_bytes());
+        write_dict(&mut shape, &[]);
+        write_chunk(&mut children, b"nSHP", &shape);
+    }
+
+    let mut palette = Vec::with_capacity(256 * 4);
+    for i in 1..256 {
+        palette.extend_from_slice(&scene.palette[i]);
+    }
+    palette.extend_from_slice(&[0, 0, 0, 0]);
+    write_chunk(&mut children, b"RGBA", &palette);
+
+    let mut data = Vec::with_capacity(children.len() + 20);
+    data.extend_from_slice(b"VOX ");
+    data.extend_from_slice(&150i32.to_le_bytes());
+    data.extend_from_slice(b"MAIN");
+    data.extend_from_slice(&0i32.to_le_bytes());
+    data.extend_from_slice(&(children.len() as i32).to_le_bytes());
+    data.extend_from_slice(&children);
+    return data;
+}
+
+// palette index with the color closest to the given rgb, never the empty index 0
+pub fn nearest_palette_index(palette: &[[u8; 4]; 256], color: [u8; 3]) -> u8 {
+    let mut best = 1;
+    let mut best_distance = i32::MAX;
+    for i in 1..256 {
+        let distance: i32 = (0..3)
+            .map(|c| (palette[i][c] as i32 - color[c] as i32).pow(2))
+            .sum();
+        if distance < best_distance {
+            best = i;
+            best_distance = distance;
+        }
+    }
+    return best as u8;
+}
+
+fn collect_shapes(nodes: &HashMap<i32, SceneNode>, id: i32, offset: [i32; 3], out: &mut Vec<(i32, [i32; 3])>, depth: u32) {
+    // guards against cycles in broken files
+    if depth > 64 {
+        return;
+    }
+    match nodes.get(&id) {
+        Some(SceneNode::Transform { child, translation }) => {
+            let offset = [
+                offset[0].saturating_add(translation[0]),
+                offset[1].saturating_add(translation[1]),
+                offset[2].saturating_add(translation[2]),
+            ];
+            collect_shapes(nodes, *child, offset, out, depth + 1);
+        }
+        Some(SceneNode::Group { children }) => {
+            for child in children {
+                collect_shapes(nodes, *child, offset, out, depth + 1);
+            }
+        }
+        Some(SceneNode::Shape { models }) => {
+            for model in models {
+                out.push((*model, offset));
+            }
+        }
+        None => {}
+    }
+}
+
+// only used for files without an RGBA chunk
+fn fallback_palette() -> [[u8; 4]; 256] {
+    let mut palette = [[0u8; 4]; 256];
+    for i in 1..256 {
+        palette[i] = [i as u8, i as u8, i as u8, 255];
+    }
+    return palette;
+}
+
+fn write_chunk(out: &mut Vec<u8>, id: &[u8; 4], content: &[u8]) {
+    out.extend_from_slice(id);
+    out.extend_from_slice(&(content.len() as i32).to_le_bytes());
+    out.extend_from_slice(&0i32.to_le_bytes());
+    out.extend_from_slice(content);
+}
+
+fn write_transform_node(out: &mut Vec<u8>, id: i32, child: i32, translation: [i32; 3]) {
+    out.extend_from_slice(&id.to_le_bytes());
+    write_dict(out, &[]);
+    out.extend_from_slice(&child.to_le_bytes());
+    out.extend_from_slice(&(-1i32).to_le_bytes());
+    out.extend_from_slice(&0i32.to_le_bytes());
+    out.extend_from_slice(&1i32.to_le_bytes());
+    let t = format!("{} {} {}", translation[0], translation[1], translation[2]);
+    write_dict(out, &[("_t", &t)]);
+}
+
+fn write_dict(out: &mut Vec<u8>, entries: &[(&str, &str)]) {
+    out.extend_from_slice(&(entries.len() as i32).to_le_bytes());
+    for (key, value) in entries {
+        for s in [key, value].iter() {
+            out.extend_from_slice(&(s.len() as i32).to_le_bytes());
+            out.extend_from_slice(s.as_bytes());
+        }
+    }
+}
+
+fn invalid(message: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, message)
+}
+
+struct ByteReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = match self.pos.checked_add(len) {
+            Some(end) if end <= self.data.len() => end,
+            _ => return Err(Error::new(ErrorKind::UnexpectedEof, "vox file ends in the middle of a chunk")),
+        };
+        let slice = &self.data[self.pos..end];
+        self.pos = end;
+        return Ok(slice);
+    }
+    fn i32(&mut self) -> Result<i32> {
+        Ok(i32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+    // sizes and counts are stored as i32, a negative one can only come from a broken file
+    fn len(&mut self) -> Result<usize> {
+        let len = self.i32()?;
+        if len < 0 {
+            return Err(invalid("negative size or count"));
+        }
+        Ok(len as usize)
+    }
+    fn string(&mut self) -> Result<String> {
+        let len = self.len()?;
+        Ok(String::from_utf8_lossy(self.take(len)?).into_owned())
+    }
+    fn dict(&mut self) -> Result<HashMap<String, String>> {
+        let count = self.i32()?;
+        let mut dict = HashMap::new();
+        for _ in 0..count.max(0) {
+            let key = self.string()?;
+            let value = self.string()?;
+            dict.insert(key, value);
+        }
+        Ok(dict)
+    }
+}
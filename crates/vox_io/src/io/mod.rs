@@ -1,2 +1,11 @@
+pub mod compression;
+pub mod config_reader;
+pub mod error;
 pub mod file_reader;
 pub mod file_writer;
+pub mod image_reader;
+pub mod image_writer;
+pub mod magica_voxel;
+pub mod nbt;
+pub mod save_format;
+pub mod sponge_schematic;
@@ -0,0 +1,205 @@
+use std::convert::TryInto;
+use std::io::{Error, ErrorKind, Result};
+
+// named binary tag values, as used by Minecraft and the tools around it
+#[derive(Debug, Clone, PartialEq)]
+pub enum Tag {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    ByteArray(Vec<i8>),
+    String(String),
+    // the element type id is kept so empty lists survive a round trip
+    List(u8, Vec<Tag>),
+    Compound(Vec<(String, Tag)>),
+    IntArray(Vec<i32>),
+    LongArray(Vec<i64>),
+}
+
+impl Tag {
+    pub fn id(&self) -> u8 {
+        match self {
+            Tag::Byte(_) => 1,
+            Tag::Short(_) => 2,
+            Tag::Int(_) => 3,
+            Tag::Long(_) => 4,
+            Tag::Float(_) => 5,
+            Tag::Double(_) => 6,
+            Tag::ByteArray(_) => 7,
+            Tag::String(_) => 8,
+            Tag::List(_, _) => 9,
+            Tag::Compound(_) => 10,
+            Tag::IntArray(_) => 11,
+            Tag::LongArray(_) => 12,
+        }
+    }
+    pub fn get(&self, name: &str) -> Option<&Tag> {
+        match self {
+            Tag::Compound(entries) => entries.iter().find(|(n, _)| n == name).map(|(_, t)| t),
+            _ => None,
+        }
+    }
+    // integer tags of any width
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Tag::Byte(v) => Some(*v as i64),
+            Tag::Short(v) => Some(*v as i64),
+            Tag::Int(v) => Some(*v as i64),
+            Tag::Long(v) => Some(*v),
+            _ => None,
+        }
+    }
+}
+
+// returns the name and value of the root tag
+pub fn read_nbt(data: &[u8]) -> Result<(String, Tag)> {
+    let mut reader = NbtReader { data, pos: 0 };
+    let id = reader.u8()?;
+    if id != 10 {
+        return Err(Error::new(ErrorKind::InvalidData, "nbt root is not a compound"));
+    }
+    let name = reader.string()?;
+    let tag = reader.payload(id, 0)?;
+    return Ok((name, tag));
+}
+
+pub fn write_nbt(name: &str, tag: &Tag) -> Vec<u8> {
+    let mut out = Vec::new();
+    out.push(tag.id());
+    write_string(&mut out, name);
+    write_payload(&mut out, tag);
+    return out;
+}
+
+fn write_string(out: &mut Vec<u8>, s: &str) {
+    out.extend_from_slice(&(s.len() as u16).to_be_bytes());
+    out.extend_from_slice(s.as_bytes());
+}
+
+fn write_payload(out: &mut Vec<u8>, tag: &Tag) {
+    match tag {
+        Tag::Byte(v) => out.push(*v as u8),
+        Tag::Short(v) => out.extend_from_slice(&v.to_be_bytes()),
+        Tag::Int(v) => out.extend_from_slice(&v.to_be_bytes()),
+        Tag::Long(v) => out.extend_from_slice(&v.to_be_bytes()),
+        Tag::Float(v) => out.extend_from_slice(&v.to_be_bytes()),
+        Tag::Double(v) => out.extend_from_slice(&v.to_be_bytes()),
+        Tag::ByteArray(values) => {
+            out.extend_from_slice(&(values.len() as i32).to_be_bytes());
+            out.extend(values.iter().map(|v| *v as u8));
+        }
+        Tag::String(s) => write_string(out, s),
+        Tag::List(id, values) => {
+            out.push(if values.is_empty() { *id } else { values[0].id() });
+            out.extend_from_slice(&(values.len() as i32).to_be_bytes());
+            for value in values {
+                write_payload(out, value);
+            }
+        }
+        Tag::Compound(entries) => {
+            for (name, value) in entries {
+                out.push(value.id());
+                write_string(out, name);
+                write_payload(out, value);
+            }
+            out.push(0);
+        }
+        Tag::IntArray(values) => {
+            out.extend_from_slice(&(values.len() as i32).to_be_bytes());
+            for v in values {
+                out.extend_from_slice(&v.to_be_bytes());
+            }
+        }
+        Tag::LongArray(values) => {
+            out.extend_from_slice(&(values.len() as i32).to_be_bytes());
+            for v in values {
+                out.extend_from_slice(&v.to_be_bytes());
+            }
+        }
+    }
+}
+
+struct NbtReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> NbtReader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = match self.pos.checked_add(len) {
+            Some(end) if end <= self.data.len() => end,
+            _ => return Err(Error::new(ErrorKind::UnexpectedEof, "nbt data ends in the middle of a tag")),
+        };
+        let slice = &self.data[self.pos..end];
+        self.pos = end;
+        return Ok(slice);
+    }
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+    fn length(&mut self) -> Result<usize> {
+        let len = i32::from_be_bytes(self.take(4)?.try_into().unwrap());
+        if len < 0 {
+            return Err(Error::new(ErrorKind::InvalidData, "negative nbt length"));
+        }
+        Ok(len as usize)
+    }
+    fn string(&mut self) -> Result<String> {
+        let len = u16::from_be_bytes(self.take(2)?.try_into().unwrap()) as usize;
+        Ok(String::from_utf8_lossy(self.take(len)?).into_owned())
+    }
+    fn payload(&mut self, id: u8, depth: u32) -> Result<Tag> {
+        if depth > 512 {
+            return Err(Error::new(ErrorKind::InvalidData, "nbt nested too deep"));
+        }
+        let tag = match id {
+            1 => Tag::Byte(self.u8()? as i8),
+            2 => Tag::Short(i16::from_be_bytes(self.take(2)?.try_into().unwrap())),
+            3 => Tag::Int(i32::from_be_bytes(self.take(4)?.try_into().unwrap())),
+            4 => Tag::Long(i64::from_be_bytes(self.take(8)?.try_into().unwrap())),
+            5 => Tag::Float(f32::from_be_bytes(self.take(4)?.try_into().unwrap())),
+            6 => Tag::Double(f64::from_be_bytes(self.take(8)?.try_into().unwrap())),
+            7 => {
+                let len = self.length()?;
+                Tag::ByteArray(self.take(len)?.iter().map(|b| *b as i8).collect())
+            }
+            8 => Tag::String(self.string()?),
+            9 => {
+                let element_id = self.u8()?;
+                let len = self.length()?;
+                let mut values = Vec::new();
+                for _ in 0..len {
+                    values.push(self.payload(element_id, depth + 1)?);
+                }
+                Tag::List(element_id, values)
+            }
+            10 => {
+                let mut entries = Vec::new();
+                loop {
+                    let entry_id = self.u8()?;
+                    if entry_id == 0 {
+                        break;
+                    }
+                    let name = self.string()?;
+                    entries.push((name, self.payload(entry_id, depth + 1)?));
+                }
+                Tag::Compound(entries)
+            }
+            11 => {
+                let len = self.length()?;
+                let bytes = self.take(len * 4)?;
+                Tag::IntArray(bytes.chunks_exact(4).map(|b| i32::from_be_bytes(b.try_into().unwrap())).collect())
+            }
+            12 => {
+                let len = self.length()?;
+                let bytes = self.take(len * 8)?;
+                Tag::LongArray(bytes.chunks_exact(8).map(|b| i64::from_be_bytes(b.try_into().unwrap())).collect())
+            }
+            _ => return Err(Error::new(ErrorKind::InvalidData, format!("unknown nbt tag id {}", id))),
+        };
+        return Ok(tag);
+    }
+}
@@ -0,0 +1,180 @@
+use crate::io::compression::{compress_timed, Codec, CompressionStats};
+use crate::io::error::VoxIoError;
+use crate::io::file_writer::write_bytes_to_file;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::convert::TryInto;
+use std::path::Path;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+// every save file written by vox_io starts with these bytes followed by the format version as
+// a little endian u32, files from before the header existed are treated as version 0 but only
+// when they decode without it, otherwise the header is damaged
+// since version 2 the payload starts with the id of the codec the rest of it is compressed with
+// since version 3 that is preceded by a little endian crc32 of everything after it
+pub const MAGIC: [u8; 4] = *b"VOXS";
+pub const FORMAT_VERSION: u32 = 3;
+pub const HEADER_SIZE: usize = 8;
+pub const QUARANTINE_DIR: &str = "quarantine";
+
+// upgrades the payload of a file from one version to the next
+pub type Migration = fn(Vec<u8>) -> Result<Vec<u8>, String>;
+
+pub struct Migrations {
+    // keyed by the version the migration upgrades from
+    steps: BTreeMap<u32, Migration>,
+}
+
+impl Migrations {
+    pub fn new() -> Migrations {
+        Migrations { steps: BTreeMap::new() }
+    }
+    pub fn register(&mut self, from_version: u32, migration: Migration) {
+        self.steps.insert(from_version, migration);
+    }
+    // applies the migrations one version at a time until the payload is at FORMAT_VERSION
+    pub fn upgrade(&self, filename: &str, version: u32, payload: Vec<u8>) -> Result<Vec<u8>, VoxIoError> {
+        if version > FORMAT_VERSION {
+            return Err(VoxIoError::VersionMismatch {
+                filename: filename.to_string(),
+                found: version,
+                expected: FORMAT_VERSION,
+            });
+        }
+        let mut payload = payload;
+        for from in version..FORMAT_VERSION {
+            let migration = match self.steps.get(&from) {
+                Some(m) => m,
+                None => {
+                    return Err(VoxIoError::VersionMismatch {
+                        filename: filename.to_string(),
+                        found: version,
+                        expected: FORMAT_VERSION,
+                    })
+                }
+            };
+            payload = migration(payload).map_err(|e| {
+                VoxIoError::Corrupt(filename.to_string(), format!("migration from version {} failed: {}", from, e))
+            })?;
+        }
+        return Ok(payload);
+    }
+}
+
+impl Default for Migrations {
+    fn default() -> Self {
+        let mut migrations = Migrations::new();
+        // version 1 only added the header, the bincode payload is unchanged
+        migrations.register(0, |payload| Ok(payload));
+        // version 2 added the codec byte, older payloads are uncompressed
+        migrations.register(1, |mut payload| {
+            payload.insert(0, Codec::None.id());
+            Ok(payload)
+        });
+        // version 3 added the checksum
+        migrations.register(2, |payload| {
+            let mut upgraded = crc32fast::hash(&payload).to_le_bytes().to_vec();
+            upgraded.extend_from_slice(&payload);
+            Ok(upgraded)
+        });
+        migrations
+    }
+}
+
+pub fn write_header(out: &mut Vec<u8>) {
+    out.extend_from_slice(&MAGIC);
+    out.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+}
+
+// returns the format version and the payload after the header, None when there is no header
+pub fn split_header(data: &[u8]) -> Option<(u32, &[u8])> {
+    if data.len() < HEADER_SIZE || data[..4] != MAGIC {
+        return None;
+    }
+    let version = u32::from_le_bytes(data[4..HEADER_SIZE].try_into().unwrap());
+    return Some((version, &data[HEADER_SIZE..]));
+}
+
+// header, checksum, codec id and the compressed payload
+pub fn encode_file(codec: Codec, raw: &[u8]) -> (Vec<u8>, CompressionStats) {
+    let (compressed, stats) = compress_timed(codec, raw);
+    let mut hasher = crc32fast::Hasher::new();
+    hasher.update(&[codec.id()]);
+    hasher.update(&compressed);
+    let mut data = Vec::with_capacity(HEADER_SIZE + 5 + compressed.len());
+    write_header(&mut data);
+    data.extend_from_slice(&hasher.finalize().to_le_bytes());
+    data.push(codec.id());
+    data.extend_from_slice(&compressed);
+    return (data, stats);
+}
+
+// upgrades and decompresses the contents of a file, the stats hold the time spent decompressing
+// is_legacy tells whether data without a header is a file from before the header existed
+pub fn decode_file(
+    filename: &str,
+    data: &[u8],
+    migrations: &Migrations,
+    is_legacy: impl Fn(&[u8]) -> bool,
+) -> Result<(Vec<u8>, CompressionStats), VoxIoError> {
+    let (version, payload) = match split_header(data) {
+        Some(header) => header,
+        None if is_legacy(data) => (0, data),
+        None => return Err(VoxIoError::Corrupt(filename.to_string(), "missing or damaged header".to_string())),
+    };
+    let payload = migrations.upgrade(filename, version, payload.to_vec())?;
+    if payload.len() < 5 {
+        return Err(VoxIoError::Corrupt(filename.to_string(), "missing checksum".to_string()));
+    }
+    let checksum = u32::from_le_bytes(payload[..4].try_into().unwrap());
+    let payload = &payload[4..];
+    if crc32fast::hash(payload) != checksum {
+        return Err(VoxIoError::Corrupt(filename.to_string(), "checksum mismatch".to_string()));
+    }
+    let codec = match Codec::from_id(payload[0]) {
+        Some(c) => c,
+        None => return Err(VoxIoError::Corrupt(filename.to_string(), format!("unknown codec {}", payload[0]))),
+    };
+    let timer = Instant::now();
+    let raw = codec
+        .decompress(&payload[1..])
+        .map_err(|e| VoxIoError::Corrupt(filename.to_string(), e))?;
+    let stats = CompressionStats {
+        codec,
+        raw_size: raw.len(),
+        compressed_size: payload.len() - 1,
+        time: timer.elapsed().as_secs_f32(),
+    };
+    return Ok((raw, stats));
+}
+
+// used as the legacy check, files from before the header are plain bincode
+pub fn is_bincode<T: for<'de> Deserialize<'de>>(data: &[u8]) -> bool {
+    bincode::deserialize::<T>(data).is_ok()
+}
+
+// moves a file that can't be read into the quarantine directory next to it, so it can be
+// inspected later instead of being overwritten, returns the new location
+pub fn quarantine_file(filename: &str) -> Result<String, VoxIoError> {
+    let path = Path::new(filename);
+    let directory = path.parent().unwrap_or(Path::new(".")).join(QUARANTINE_DIR);
+    std::fs::create_dir_all(&directory).map_err(|e| VoxIoError::Io(filename.to_string(), e))?;
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let target = directory.join(format!("{}.{}", name, timestamp));
+    std::fs::rename(path, &target).map_err(|e| VoxIoError::from_io(filename, e))?;
+    return Ok(target.to_string_lossy().into_owned());
+}
+
+// rewrites a save file of any known version with the current version and the given codec,
+// returns the stats of decompressing the old file and of compressing the new one
+pub fn recompress_file<T: for<'de> Deserialize<'de>>(
+    filename: &str,
+    codec: Codec,
+) -> Result<(CompressionStats, CompressionStats), VoxIoError> {
+    let data = std::fs::read(filename).map_err(|e| VoxIoError::from_io(filename, e))?;
+    let (raw, before) = decode_file(filename, &data, &Migrations::default(), is_bincode::<T>)?;
+    let (data, after) = encode_file(codec, &raw);
+    write_bytes_to_file(filename, &data)?;
+    return Ok((before, after));
+}
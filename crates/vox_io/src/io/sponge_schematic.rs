@@ -0,0 +1,199 @@
+use crate::io::error::VoxIoError;
+use crate::io::nbt::{read_nbt, write_nbt, Tag};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::fs::File;
+use std::io::{BufWriter, Error, ErrorKind, Read, Result, Write};
+
+// block data of a Sponge schematic, version 2 or 3, block entities and entities are not kept
+pub struct SpongeSchematic {
+    pub version: i32,
+    pub data_version: i32,
+    pub width: u16,
+    pub height: u16,
+    pub length: u16,
+    pub offset: [i32; 3],
+    // block state names like "minecraft:oak_log[axis=y]", indexed by the values in blocks
+    pub palette: Vec<String>,
+    // palette index per block, ordered x first, then z, then y
+    pub blocks: Vec<u32>,
+}
+
+impl SpongeSchematic {
+    pub fn index(&self, x: u32, y: u32, z: u32) -> usize {
+        (x + z * self.width as u32 + y * self.width as u32 * self.length as u32) as usize
+    }
+}
+
+pub fn read_schematic_from_file(filename: &str) -> std::result::Result<SpongeSchematic, VoxIoError> {
+    let mut f = File::open(filename).map_err(|e| VoxIoError::from_io(filename, e))?;
+    let mut data = Vec::new();
+    f.read_to_end(&mut data).map_err(|e| VoxIoError::from_io(filename, e))?;
+    return parse_schematic(&data).map_err(|e| VoxIoError::from_parser(filename, e));
+}
+
+pub fn write_schematic_to_file(filename: &str, schematic: &SpongeSchematic) -> std::result::Result<(), VoxIoError> {
+    let file = File::create(filename).map_err(|e| VoxIoError::from_io(filename, e))?;
+    let mut encoder = GzEncoder::new(BufWriter::new(file), Compression::default());
+    encoder.write_all(&encode_schematic(schematic)).map_err(|e| VoxIoError::Io(filename.to_string(), e))?;
+    let mut writer = encoder.finish().map_err(|e| VoxIoError::Io(filename.to_string(), e))?;
+    writer.flush().map_err(|e| VoxIoError::Io(filename.to_string(), e))?;
+    return Ok(());
+}
+
+// accepts gzip compressed and plain nbt
+pub fn parse_schematic(data: &[u8]) -> Result<SpongeSchematic> {
+    let mut decompressed = Vec::new();
+    let nbt = if data.starts_with(&[0x1f, 0x8b]) {
+        GzDecoder::new(data).read_to_end(&mut decompressed)?;
+        &decompressed[..]
+    } else {
+        data
+    };
+    let (_, root) = read_nbt(nbt)?;
+    // version 3 wraps everything in a Schematic compound
+    let schematic = match root.get("Schematic") {
+        Some(inner) => inner,
+        None => &root,
+    };
+    let version = get_int(schematic, "Version")? as i32;
+    let (palette_tag, data_tag) = match version {
+        2 => (schematic.get("Palette"), schematic.get("BlockData")),
+        3 => {
+            let blocks = schematic.get("Blocks").ok_or_else(|| invalid("missing Blocks"))?;
+            (blocks.get("Palette"), blocks.get("Data"))
+        }
+        _ => return Err(invalid(&format!("unsupported schematic version {}", version))),
+    };
+    let width = get_int(schematic, "Width")? as u16;
+    let height = get_int(schematic, "Height")? as u16;
+    let length = get_int(schematic, "Length")? as u16;
+    let offset = match schematic.get("Offset") {
+        Some(Tag::IntArray(o)) if o.len() == 3 => [o[0], o[1], o[2]],
+        _ => [0, 0, 0],
+    };
+
+    let entries = match palette_tag {
+        Some(Tag::Compound(entries)) => entries,
+        _ => return Err(invalid("missing block palette")),
+    };
+    let mut palette = vec![String::new(); entries.len()];
+    for (name, index) in entries {
+        let index = index.as_i64().ok_or_else(|| invalid("palette index is not an integer"))?;
+        if index < 0 || index >= entries.len() as i64 {
+            return Err(invalid(&format!("palette index {} of {} is out of range", index, name)));
+        }
+        palette[index as usize] = name.clone();
+    }
+
+    let bytes = match data_tag {
+        Some(Tag::ByteArray(b)) => b,
+        _ => return Err(invalid("missing block data")),
+    };
+    let blocks = decode_varints(bytes)?;
+    let volume = width as usize * height as usize * length as usize;
+    if blocks.len() != volume {
+        return Err(invalid("block data does not match the schematic size"));
+    }
+    if blocks.iter().any(|b| *b as usize >= palette.len()) {
+        return Err(invalid("block data refers to a missing palette entry"));
+    }
+    return Ok(SpongeSchematic {
+        version,
+        data_version: schematic.get("DataVersion").and_then(|t| t.as_i64()).unwrap_or(0) as i32,
+        width,
+        height,
+        length,
+        offset,
+        palette,
+        blocks,
+    });
+}
+
+pub fn encode_schematic(schematic: &SpongeSchematic) -> Vec<u8> {
+    let palette = Tag::Compound(
+        schematic
+            .palette
+            .iter()
+            .enumerate()
+            .map(|(i, name)| (name.clone(), Tag::Int(i as i32)))
+            .collect(),
+    );
+    let data = Tag::ByteArray(encode_varints(&schematic.blocks));
+    let mut entries = vec![
+        ("Version".to_string(), Tag::Int(schematic.version)),
+        ("DataVersion".to_string(), Tag::Int(schematic.data_version)),
+        ("Width".to_string(), Tag::Short(schematic.width as i16)),
+        ("Height".to_string(), Tag::Short(schematic.height as i16)),
+        ("Length".to_string(), Tag::Short(schematic.length as i16)),
+        ("Offset".to_string(), Tag::IntArray(schematic.offset.to_vec())),
+    ];
+    if schematic.version >= 3 {
+        entries.push((
+            "Blocks".to_string(),
+            Tag::Compound(vec![
+                ("Palette".to_string(), palette),
+                ("Data".to_string(), data),
+                ("BlockEntities".to_string(), Tag::List(10, Vec::new())),
+            ]),
+        ));
+        let root = Tag::Compound(vec![("Schematic".to_string(), Tag::Compound(entries))]);
+        return write_nbt("", &root);
+    }
+    entries.push(("PaletteMax".to_string(), Tag::Int(schematic.palette.len() as i32)));
+    entries.push(("Palette".to_string(), palette));
+    entries.push(("BlockData".to_string(), data));
+    entries.push(("BlockEntities".to_string(), Tag::List(10, Vec::new())));
+    return write_nbt("Schematic", &Tag::Compound(entries));
+}
+
+fn decode_varints(bytes: &[i8]) -> Result<Vec<u32>> {
+    let mut values = Vec::with_capacity(bytes.len());
+    let mut value = 0u32;
+    let mut shift = 0;
+    for b in bytes {
+        let b = *b as u8;
+        value |= ((b & 0x7f) as u32) << shift;
+        if b & 0x80 == 0 {
+            values.push(value);
+            value = 0;
+            shift = 0;
+        } else {
+            shift += 7;
+            if shift > 28 {
+                return Err(invalid("varint in block data is too long"));
+            }
+        }
+    }
+    if shift != 0 {
+        return Err(invalid("block data ends in the middle of a varint"));
+    }
+    return Ok(values);
+}
+
+fn encode_varints(values: &[u32]) -> Vec<i8> {
+    let mut bytes = Vec::with_capacity(values.len());
+    for value in values {
+        let mut v = *value;
+        loop {
+            if v < 0x80 {
+                bytes.push(v as u8 as i8);
+                break;
+            }
+            bytes.push(((v & 0x7f) as u8 | 0x80) as i8);
+            v >>= 7;
+        }
+    }
+    return bytes;
+}
+
+fn get_int(tag: &Tag, name: &str) -> Result<i64> {
+    tag.get(name)
+        .and_then(|t| t.as_i64())
+        .ok_or_else(|| invalid(&format!("missing {}", name)))
+}
+
+fn invalid(message: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, message.to_string())
+}
@@ -0,0 +1,105 @@
+use std::io::ErrorKind;
+use vox_io::io::error::VoxIoError;
+use vox_io::io::magica_voxel::{encode_vox, parse_vox, read_vox_from_file, write_vox_to_file, VoxModel, VoxScene};
+
+fn test_scene() -> VoxScene {
+    let mut palette = [[0u8; 4]; 256];
+    for i in 1..256 {
+        palette[i] = [i as u8, 255 - i as u8, (i * 7) as u8, 255];
+    }
+    VoxScene {
+        models: vec![
+            VoxModel {
+                size: [4, 6, 2],
+                voxels: vec![[0, 0, 0, 1], [3, 5, 1, 200], [2, 1, 0, 17]],
+                position: [-2, -3, 0],
+            },
+            VoxModel {
+                size: [1, 1, 1],
+                voxels: vec![[0, 0, 0, 255]],
+                position: [10, 20, 30],
+            },
+        ],
+        palette,
+    }
+}
+
+fn check_scene(scene: &VoxScene) {
+    let expected = test_scene();
+    assert_eq!(scene.models.len(), expected.models.len());
+    for (model, expected) in scene.models.iter().zip(expected.models.iter()) {
+        assert_eq!(model.size, expected.size);
+        assert_eq!(model.voxels, expected.voxels);
+        assert_eq!(model.position, expected.position);
+    }
+    assert_eq!(scene.palette[1..], expected.palette[1..]);
+}
+
+// patches the first i32 after the given chunk id
+fn set_chunk_field(data: &mut Vec<u8>, id: &[u8; 4], field: usize, value: i32) {
+    let start = data.windows(4).position(|w| w == id).unwrap() + 4 + field * 4;
+    data[start..start + 4].copy_from_slice(&value.to_le_bytes());
+}
+
+fn parse_error(data: &[u8]) -> ErrorKind {
+    match parse_vox(data) {
+        Ok(_) => panic!("malformed vox data was accepted"),
+        Err(e) => e.kind(),
+    }
+}
+
+#[test]
+fn scenes_round_trip() {
+    let data = encode_vox(&test_scene());
+    check_scene(&parse_vox(&data).unwrap());
+}
+
+#[test]
+fn files_round_trip() {
+    let filename = std::env::temp_dir().join("vox_magica_voxel_test.vox");
+    let filename = filename.to_str().unwrap();
+    write_vox_to_file(filename, &test_scene()).unwrap();
+    check_scene(&read_vox_from_file(filename).unwrap());
+    std::fs::remove_file(filename).unwrap();
+}
+
+#[test]
+fn malformed_files_are_rejected() {
+    let data = encode_vox(&test_scene());
+
+    assert_eq!(parse_error(b"VOX"), ErrorKind::UnexpectedEof);
+    assert_eq!(parse_error(&data[..data.len() - 10]), ErrorKind::UnexpectedEof);
+    let mut bad_magic = data.clone();
+    bad_magic[0] = b'X';
+    assert_eq!(parse_error(&bad_magic), ErrorKind::InvalidData);
+
+    // the content of XYZI starts with the voxel count, after the content and children sizes
+    let mut negative_count = data.clone();
+    set_chunk_field(&mut negative_count, b"XYZI", 2, -1);
+    assert_eq!(parse_error(&negative_count), ErrorKind::InvalidData);
+    let mut huge_count = data.clone();
+    set_chunk_field(&mut huge_count, b"XYZI", 2, i32::MAX);
+    assert_eq!(parse_error(&huge_count), ErrorKind::UnexpectedEof);
+
+    let mut negative_content = data.clone();
+    set_chunk_field(&mut negative_content, b"SIZE", 0, -4);
+    assert_eq!(parse_error(&negative_content), ErrorKind::InvalidData);
+    let mut huge_children = data.clone();
+    set_chunk_field(&mut huge_children, b"RGBA", 1, i32::MAX);
+    assert_eq!(parse_error(&huge_children), ErrorKind::UnexpectedEof);
+    let mut negative_size = data.clone();
+    set_chunk_field(&mut negative_size, b"SIZE", 3, -6);
+    assert_eq!(parse_error(&negative_size), ErrorKind::InvalidData);
+}
+
+#[test]
+fn corrupt_files_are_reported_as_corrupt() {
+    let filename = std::env::temp_dir().join("vox_magica_voxel_corrupt.vox");
+    let filename = filename.to_str().unwrap();
+    std::fs::write(filename, b"VOX \x96\x00\x00\x00MAIN\xff\xff\xff\xff").unwrap();
+    match read_vox_from_file(filename) {
+        Err(VoxIoError::Corrupt(_, _)) => {}
+        other => panic!("expected a corrupt file, got {:?}", other.err()),
+    }
+    std::fs::remove_file(filename).unwrap();
+}
@@ -0,0 +1,109 @@
+use std::io::ErrorKind;
+use vox_io::io::nbt::{read_nbt, write_nbt, Tag};
+use vox_io::io::sponge_schematic::{
+    encode_schematic, parse_schematic, read_schematic_from_file, write_schematic_to_file, SpongeSchematic,
+};
+
+fn test_schematic(version: i32) -> SpongeSchematic {
+    let (width, height, length) = (3, 2, 4);
+    SpongeSchematic {
+        version,
+        data_version: 2975,
+        width,
+        height,
+        length,
+        offset: [-1, 0, 2],
+        palette: (0..200).map(|i| format!("minecraft:block_{}", i)).collect(),
+        // indices past 127 take two bytes as a varint
+        blocks: (0..(width * height * length) as u32).map(|i| i * 7 % 200).collect(),
+    }
+}
+
+fn check_schematic(schematic: &SpongeSchematic, expected: &SpongeSchematic) {
+    assert_eq!(schematic.version, expected.version);
+    assert_eq!(schematic.data_version, expected.data_version);
+    assert_eq!(
+        [schematic.width, schematic.height, schematic.length],
+        [expected.width, expected.height, expected.length]
+    );
+    assert_eq!(schematic.offset, expected.offset);
+    assert_eq!(schematic.palette, expected.palette);
+    assert_eq!(schematic.blocks, expected.blocks);
+}
+
+// a version 2 schematic with a hand made palette, the block data is all index 0
+fn v2_with_palette(palette: Vec<(String, Tag)>) -> Vec<u8> {
+    let root = Tag::Compound(vec![
+        ("Version".to_string(), Tag::Int(2)),
+        ("Width".to_string(), Tag::Short(1)),
+        ("Height".to_string(), Tag::Short(1)),
+        ("Length".to_string(), Tag::Short(2)),
+        ("Palette".to_string(), Tag::Compound(palette)),
+        ("BlockData".to_string(), Tag::ByteArray(vec![0, 0])),
+    ]);
+    return write_nbt("Schematic", &root);
+}
+
+#[test]
+fn nbt_round_trips() {
+    let root = Tag::Compound(vec![
+        ("byte".to_string(), Tag::Byte(-3)),
+        ("short".to_string(), Tag::Short(-300)),
+        ("int".to_string(), Tag::Int(1 << 20)),
+        ("long".to_string(), Tag::Long(-(1 << 40))),
+        ("float".to_string(), Tag::Float(1.5)),
+        ("double".to_string(), Tag::Double(-2.25)),
+        ("bytes".to_string(), Tag::ByteArray(vec![-1, 0, 127])),
+        ("string".to_string(), Tag::String("minecraft:stone".to_string())),
+        ("list".to_string(), Tag::List(3, vec![Tag::Int(1), Tag::Int(2)])),
+        ("empty list".to_string(), Tag::List(10, Vec::new())),
+        (
+            "compound".to_string(),
+            Tag::Compound(vec![("inner".to_string(), Tag::Byte(1))]),
+        ),
+        ("ints".to_string(), Tag::IntArray(vec![i32::MIN, 0, i32::MAX])),
+        ("longs".to_string(), Tag::LongArray(vec![i64::MIN, i64::MAX])),
+    ]);
+    let data = write_nbt("root", &root);
+    assert_eq!(read_nbt(&data).unwrap(), ("root".to_string(), root));
+    for len in 0..data.len() {
+        assert!(read_nbt(&data[..len]).is_err());
+    }
+}
+
+#[test]
+fn every_version_round_trips() {
+    for version in [2, 3].iter() {
+        let schematic = test_schematic(*version);
+        check_schematic(&parse_schematic(&encode_schematic(&schematic)).unwrap(), &schematic);
+
+        let filename = std::env::temp_dir().join(format!("vox_schematic_v{}.schem", version));
+        let filename = filename.to_str().unwrap();
+        write_schematic_to_file(filename, &schematic).unwrap();
+        check_schematic(&read_schematic_from_file(filename).unwrap(), &schematic);
+        std::fs::remove_file(filename).unwrap();
+    }
+}
+
+#[test]
+fn out_of_range_palette_indices_are_rejected() {
+    let valid = v2_with_palette(vec![("minecraft:air".to_string(), Tag::Int(0))]);
+    assert_eq!(parse_schematic(&valid).unwrap().palette, vec!["minecraft:air".to_string()]);
+    for index in [-1, 1, i32::MAX].iter() {
+        let data = v2_with_palette(vec![("minecraft:air".to_string(), Tag::Int(*index))]);
+        match parse_schematic(&data) {
+            Err(e) => assert_eq!(e.kind(), ErrorKind::InvalidData),
+            Ok(_) => panic!("palette index {} was accepted", index),
+        }
+    }
+}
+
+#[test]
+fn block_data_has_to_match_the_palette_and_size() {
+    let mut schematic = test_schematic(3);
+    schematic.blocks[5] = 200;
+    assert!(parse_schematic(&encode_schematic(&schematic)).is_err());
+    let mut schematic = test_schematic(2);
+    schematic.blocks.pop();
+    assert!(parse_schematic(&encode_schematic(&schematic)).is_err());
+}
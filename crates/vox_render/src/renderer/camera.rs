@@ -0,0 +1,98 @@
+use nalgebra::{Matrix4, Vector3};
+use std::f32::consts::PI;
+
+// looking straight up or down would make the view matrix degenerate
+pub const MAX_PITCH: f32 = 89.0 * PI / 180.0;
+
+// view space is left handed, x to the right, y up and z forward, depth is reversed so the near plane
+// ends up at 1 and the far plane at 0, which spreads the float precision much better over the distance
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Camera {
+    // radians around the y axis, 0 looks along +z and positive turns towards +x
+    pub yaw: f32,
+    // radians above the horizon, within MAX_PITCH
+    pub pitch: f32,
+    // vertical field of view in radians
+    pub fov: f32,
+    pub znear: f32,
+    pub zfar: f32,
+}
+
+impl Camera {
+    pub fn new(yaw: f32, pitch: f32) -> Camera {
+        let mut camera = Camera {
+            yaw: 0.0,
+            pitch: 0.0,
+            fov: 70.0 * PI / 180.0,
+            znear: 0.05,
+            zfar: 1024.0,
+        };
+        camera.set_rotation(yaw, pitch);
+        return camera;
+    }
+    pub fn looking_at(direction: [f32; 3]) -> Camera {
+        let mut camera = Camera::new(0.0, 0.0);
+        camera.look_in_direction(direction);
+        return camera;
+    }
+    pub fn set_rotation(&mut self, yaw: f32, pitch: f32) {
+        self.yaw = yaw.rem_euclid(2.0 * PI);
+        self.pitch = pitch.clamp(-MAX_PITCH, MAX_PITCH);
+    }
+    pub fn rotate(&mut self, yaw_diff: f32, pitch_diff: f32) {
+        self.set_rotation(self.yaw + yaw_diff, self.pitch + pitch_diff);
+    }
+    // a zero direction leaves the camera as it is
+    pub fn look_in_direction(&mut self, direction: [f32; 3]) {
+        let [x, y, z] = direction;
+        let horizontal = (x * x + z * z).sqrt();
+        if horizontal == 0.0 && y == 0.0 {
+            return;
+        }
+        let yaw = if horizontal == 0.0 { self.yaw } else { x.atan2(z) };
+        self.set_rotation(yaw, y.atan2(horizontal));
+    }
+    // unit vector the camera looks along
+    pub fn direction(&self) -> [f32; 3] {
+        [
+            self.pitch.cos() * self.yaw.sin(),
+            self.pitch.sin(),
+            self.pitch.cos() * self.yaw.cos(),
+        ]
+    }
+    // unit vector along the ground towards where the camera looks
+    pub fn forward(&self) -> [f32; 3] {
+        [self.yaw.sin(), 0.0, self.yaw.cos()]
+    }
+    pub fn right(&self) -> [f32; 3] {
+        [self.yaw.cos(), 0.0, -self.yaw.sin()]
+    }
+    pub fn view_matrix(&self, eye: [f32; 3]) -> [[f32; 4]; 4] {
+        let f = Vector3::from(self.direction());
+        let s = Vector3::from(self.right());
+        let u = f.cross(&s);
+        let eye = Vector3::from(eye);
+        [
+            [s.x, u.x, f.x, 0.0],
+            [s.y, u.y, f.y, 0.0],
+            [s.z, u.z, f.z, 0.0],
+            [-s.dot(&eye), -u.dot(&eye), -f.dot(&eye), 1.0],
+        ]
+    }
+    // aspect is width divided by height
+    pub fn projection_matrix(&self, aspect: f32) -> [[f32; 4]; 4] {
+        let f = 1.0 / (self.fov / 2.0).tan();
+        let range = self.zfar - self.znear;
+        [
+            [f / aspect, 0.0, 0.0, 0.0],
+            [0.0, f, 0.0, 0.0],
+            [0.0, 0.0, -self.znear / range, 1.0],
+            [0.0, 0.0, self.znear * self.zfar / range, 0.0],
+        ]
+    }
+    pub fn view_projection(&self, eye: [f32; 3], aspect: f32) -> [[f32; 4]; 4] {
+        let view = Matrix4::from(self.view_matrix(eye));
+        let projection = Matrix4::from(self.projection_matrix(aspect));
+        return (projection * view).into();
+    }
+}
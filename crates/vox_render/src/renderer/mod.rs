@@ -1,3 +1,4 @@
+pub mod camera;
 pub mod compute;
 pub mod depth_texture;
 pub mod renderer;
@@ -1,4 +1,3 @@
-use crate::renderer::wgpu::gen_perspective_mat;
 use nalgebra::Vector3;
 use vox_core::constants::COLORS;
 use vox_core::utils::get_rotation_matrix_y;
@@ -11,7 +10,8 @@ pub struct Uniforms {
     viewer_pos: [f32; 3],
     time: f32,
     sun_dir: [f32; 3],
-    _padding: f32,
+    // 1 when the camera is in water, tints and fogs the scene
+    underwater: f32,
     colors: [[f32; 4]; 16],
 }
 
@@ -33,7 +33,7 @@ impl Uniforms {
             viewer_pos: [0.0, 0.0, 0.0],
             sun_dir: [0.0, 0.0, 0.0],
             time: 0.0,
-            _padding: 0.0,
+            underwater: 0.0,
             colors: COLORS,
         }
     }
@@ -42,15 +42,17 @@ impl Uniforms {
         &mut self,
         viewer_pos: [f32; 3],
         view_matrix: [[f32; 4]; 4],
-        size: (u32, u32),
+        perspective: [[f32; 4]; 4],
         time: f64,
     ) {
-        let (width, height) = size;
         self.view = view_matrix;
-        self.perspective = gen_perspective_mat((width, height));
+        self.perspective = perspective;
         self.viewer_pos = viewer_pos;
         let sun_dir = get_rotation_matrix_y(time as f32) * Vector3::new(1.0, 1.0, 0.0);
         self.sun_dir = [sun_dir[0], sun_dir[1], sun_dir[2]];
         self.time = time as f32;
     }
+    pub fn set_underwater(&mut self, underwater: bool) {
+        self.underwater = if underwater { 1.0 } else { 0.0 };
+    }
 }
@@ -1,6 +1,5 @@
 use crate::renderer::depth_texture::DepthTexture;
 use futures::executor::block_on;
-use std::f32::consts::PI;
 use wgpu::{Device, Queue, Surface};
 use winit::dpi::PhysicalSize;
 use winit::window::Window;
@@ -84,21 +83,3 @@ impl WgpuState {
         self.init_surface();
     }
 }
-
-pub fn gen_perspective_mat(size: (u32, u32)) -> [[f32; 4]; 4] {
-    let (width, height) = size;
-    let aspect_ratio = height as f32 / width as f32;
-
-    let fov: f32 = PI / 3.0;
-    let zfar = 1024.0;
-    let znear = 0.0;
-
-    let f = 1.0 / (fov / 2.0).tan();
-
-    [
-        [f * aspect_ratio, 0.0, 0.0, 0.0],
-        [0.0, f, 0.0, 0.0],
-        [0.0, 0.0, (zfar + znear) / (zfar - znear), 1.0],
-        [0.0, 0.0, -(2.0 * zfar * znear) / (zfar - znear), 1.0],
-    ]
-}
@@ -80,7 +80,8 @@ impl WgpuPipeline {
             depth_stencil: Some(wgpu::DepthStencilState {
                 format: DepthTexture::DEPTH_FORMAT,
                 depth_write_enabled: true,
-                depth_compare: wgpu::CompareFunction::Less, // 1.
+                // the camera uses reversed depth, closer is larger
+                depth_compare: wgpu::CompareFunction::Greater, // 1.
                 stencil: wgpu::StencilState {
                     front: wgpu::StencilFaceState::IGNORE,
                     back: wgpu::StencilFaceState::IGNORE,
@@ -14,7 +14,7 @@ uniform Uniforms {
     vec3 viewer_pos;
     float time;
     vec3 sun_dir;
-    float _padding;
+    float underwater;
     vec4 materials[16];
 };
 
@@ -28,6 +28,9 @@ const vec3[] normals = vec3[6](
 );
 
 const vec3 diffuse_color = vec3(1.0, 1.0, 1.0);
+const vec4 water_tint = vec4(0.6, 0.75, 1.0, 1.0);
+const vec4 water_fog_color = vec4(0.05, 0.2, 0.45, 1.0);
+const float water_fog_distance = 24.0;
 
 void main() {
     vec3 perm_position = a_position;
@@ -41,5 +44,9 @@ void main() {
     float diffuse = max(dot(normalize(normals[a_normal]), normalize(sun_dir)), 0.1);
     vec4 new_color = vec4(materials[a_material][0]/255,materials[a_material][1]/255,materials[a_material][2]/255,materials[a_material][3]/255);
     v_color = new_color * vec4(diffuse_color * diffuse,1);
+    if (underwater > 0.5){
+        float fog = clamp(distance(perm_position, viewer_pos) / water_fog_distance, 0.0, 1.0);
+        v_color = mix(v_color * water_tint, water_fog_color, fog);
+    }
     gl_Position = u_perspective * u_view * vec4(perm_position, 1.0);
 }
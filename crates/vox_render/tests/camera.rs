@@ -0,0 +1,132 @@
+use std::f32::consts::PI;
+use vox_render::renderer::camera::{Camera, MAX_PITCH};
+
+const EYE: [f32; 3] = [-12.5, 40.0, 7.25];
+const ASPECT: f32 = 16.0 / 9.0;
+
+// the matrices are column major
+fn transform(m: &[[f32; 4]; 4], p: [f32; 3]) -> [f32; 4] {
+    let mut out = [0.0; 4];
+    for row in 0..4 {
+        out[row] = m[0][row] * p[0] + m[1][row] * p[1] + m[2][row] * p[2] + m[3][row];
+    }
+    return out;
+}
+
+// normalized device coordinates, x and y from -1 to 1 and depth from 1 at the near to 0 at the far plane
+fn project(camera: &Camera, p: [f32; 3]) -> [f32; 3] {
+    let clip = transform(&camera.view_projection(EYE, ASPECT), p);
+    assert!(clip[3] > 0.0, "{:?} is behind the camera", p);
+    [clip[0] / clip[3], clip[1] / clip[3], clip[2] / clip[3]]
+}
+
+fn along(dir: [f32; 3], distance: f32) -> [f32; 3] {
+    [EYE[0] + dir[0] * distance, EYE[1] + dir[1] * distance, EYE[2] + dir[2] * distance]
+}
+
+fn assert_close(a: f32, b: f32) {
+    assert!((a - b).abs() < 1e-4, "{} != {}", a, b);
+}
+
+fn cameras() -> Vec<Camera> {
+    let mut cameras = Vec::new();
+    for yaw in [0.0, 0.7, PI / 2.0, PI, 4.0, -2.5] {
+        for pitch in [0.0, 0.4, -1.1, MAX_PITCH, -MAX_PITCH] {
+            cameras.push(Camera::new(yaw, pitch));
+        }
+    }
+    return cameras;
+}
+
+#[test]
+fn pitch_is_clamped_and_yaw_wraps() {
+    let mut camera = Camera::new(0.0, 0.0);
+    camera.rotate(0.0, 10.0);
+    assert_eq!(camera.pitch, MAX_PITCH);
+    camera.rotate(0.0, -25.0);
+    assert_eq!(camera.pitch, -MAX_PITCH);
+    camera.rotate(3.0 * PI, 0.0);
+    assert_close(camera.yaw, PI);
+    camera.rotate(-1.5 * PI, 0.0);
+    assert_close(camera.yaw, 1.5 * PI);
+}
+
+#[test]
+fn looking_up_never_flips_the_camera() {
+    let mut camera = Camera::new(0.3, 0.0);
+    let forward = camera.forward();
+    for _ in 0..100 {
+        camera.rotate(0.0, 0.05);
+        let dir = camera.direction();
+        assert!(dir[0] * forward[0] + dir[2] * forward[2] > 0.0);
+        let r = camera.right();
+        let right = project(&camera, along([dir[0] + r[0], dir[1] + r[1], dir[2] + r[2]], 5.0));
+        assert!(right[0] > 0.0);
+        assert!(camera.view_projection(EYE, ASPECT).iter().flatten().all(|v| v.is_finite()));
+    }
+}
+
+#[test]
+fn directions_round_trip() {
+    for camera in cameras() {
+        let copy = Camera::looking_at(camera.direction());
+        assert_close(copy.pitch, camera.pitch);
+        let (a, b) = (copy.direction(), camera.direction());
+        for axis in 0..3 {
+            assert_close(a[axis], b[axis]);
+        }
+    }
+}
+
+#[test]
+fn the_view_direction_projects_to_the_centre() {
+    for camera in cameras() {
+        let centre = project(&camera, along(camera.direction(), 10.0));
+        assert_close(centre[0], 0.0);
+        assert_close(centre[1], 0.0);
+        // the eye itself ends up at the origin of view space
+        let view = transform(&camera.view_matrix(EYE), EYE);
+        assert_close(view[0], 0.0);
+        assert_close(view[1], 0.0);
+        assert_close(view[2], 0.0);
+    }
+}
+
+#[test]
+fn depth_is_reversed_between_the_planes() {
+    for camera in cameras() {
+        let dir = camera.direction();
+        assert_close(project(&camera, along(dir, camera.znear))[2], 1.0);
+        assert!(project(&camera, along(dir, camera.zfar))[2].abs() < 1e-6);
+        let mut last = 1.0;
+        for distance in [0.1, 1.0, 10.0, 100.0, 1000.0] {
+            let depth = project(&camera, along(dir, distance))[2];
+            assert!(depth < last && depth > 0.0);
+            last = depth;
+        }
+        let behind = transform(&camera.view_projection(EYE, ASPECT), along(dir, -5.0));
+        assert!(behind[3] < 0.0);
+    }
+}
+
+#[test]
+fn the_field_of_view_reaches_the_edges_of_the_screen() {
+    let mut camera = Camera::new(1.2, 0.3);
+    camera.fov = 90.0 * PI / 180.0;
+    let (dir, right) = (camera.direction(), camera.right());
+    let up = [
+        dir[1] * right[2] - dir[2] * right[1],
+        dir[2] * right[0] - dir[0] * right[2],
+        dir[0] * right[1] - dir[1] * right[0],
+    ];
+    let half = (camera.fov / 2.0).tan();
+    let offset = |v: [f32; 3], amount: f32| [dir[0] + v[0] * amount, dir[1] + v[1] * amount, dir[2] + v[2] * amount];
+    let top = project(&camera, along(offset(up, half), 10.0));
+    assert_close(top[0], 0.0);
+    assert_close(top[1], 1.0);
+    let side = project(&camera, along(offset(right, half * ASPECT), 10.0));
+    assert_close(side[0], 1.0);
+    assert_close(side[1], 0.0);
+    let left = project(&camera, along(offset(right, -half * ASPECT), 10.0));
+    assert_close(left[0], -1.0);
+}
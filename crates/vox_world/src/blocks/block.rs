@@ -19,6 +19,12 @@ pub const fn get_blocktype(block_id: BlockId) -> BlockType {
         4 => BlockType::Sand,
         5 => BlockType::Air,
         6 => BlockType::Leaf,
+        7 => BlockType::CoalOre,
+        8 => BlockType::IronOre,
+        9 => BlockType::GoldOre,
+        10 => BlockType::DiamondOre,
+        11 => BlockType::Log,
+        12 => BlockType::BirchLog,
         _ => BlockType::Unknown,
     }
 }
@@ -32,9 +38,39 @@ pub const fn get_blockid(block: BlockType) -> BlockId {
         BlockType::Sand => 4,
         BlockType::Air => 5,
         BlockType::Leaf => 6,
+        BlockType::CoalOre => 7,
+        BlockType::IronOre => 8,
+        BlockType::GoldOre => 9,
+        BlockType::DiamondOre => 10,
+        BlockType::Log => 11,
+        BlockType::BirchLog => 12,
         BlockType::Unknown => 255,
     }
 }
+// every block that can be in the world, air and unknown are left out
+pub fn placeable_blocks() -> Vec<BlockId> {
+    (0..=BlockId::MAX)
+        .filter(|id| {
+            let block_type = get_blocktype(*id);
+            block_type != BlockType::Unknown && block_type != BlockType::Air
+        })
+        .collect()
+}
+// blocks the player can stand on and collides with
+#[inline]
+pub const fn is_solid(block_id: BlockId) -> bool {
+    match get_blocktype(block_id) {
+        BlockType::Air | BlockType::Water | BlockType::Unknown => false,
+        _ => true,
+    }
+}
+#[inline]
+pub const fn is_fluid(block_id: BlockId) -> bool {
+    match get_blocktype(block_id) {
+        BlockType::Water => true,
+        _ => false,
+    }
+}
 #[inline]
 pub fn should_render_against(source_block_id: BlockId, neighbor_block_id: BlockId) -> bool {
     if source_block_id == neighbor_block_id {
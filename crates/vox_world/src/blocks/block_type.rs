@@ -9,4 +9,10 @@ pub enum BlockType {
     Sand,
     Air,
     Leaf,
+    CoalOre,
+    IronOre,
+    GoldOre,
+    DiamondOre,
+    Log,
+    BirchLog,
 }
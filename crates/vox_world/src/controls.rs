@@ -0,0 +1,177 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use vox_io::io::config_reader::read_config_from_file;
+use vox_io::io::error::VoxIoError;
+use winit::event::VirtualKeyCode;
+use winit_window_control::input::button::ButtonState;
+use winit_window_control::input::input::Input;
+
+pub const CONTROLS_FILE: &str = "controls.ron";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Action {
+    MoveForward,
+    MoveBack,
+    MoveLeft,
+    MoveRight,
+    // swims up in water and flies up in the fly modes
+    Jump,
+    // flies down in the fly modes
+    Descend,
+    ToggleMovementMode,
+    Break,
+    Place,
+    // selects the hotbar slot with this index
+    SelectSlot(usize),
+    Exit,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MouseButton {
+    Left,
+    Right,
+    Middle,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Binding {
+    Key(VirtualKeyCode),
+    Mouse(MouseButton),
+}
+
+// an action is held while any of its bindings is held
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ControlsConfig {
+    pub bindings: HashMap<Action, Vec<Binding>>,
+    // radians of camera rotation per pixel of mouse movement
+    pub mouse_sensitivity: f32,
+    #[serde(default)]
+    pub invert_mouse_x: bool,
+    #[serde(default)]
+    pub invert_mouse_y: bool,
+}
+
+// the actions of the current frame, this is all the player logic looks at
+#[derive(Debug, Clone, Default)]
+pub struct ActionState {
+    held: HashSet<Action>,
+    // actions that started being held this frame
+    pressed: HashSet<Action>,
+    // camera rotation in radians, around the y axis and up from the horizon
+    pub look: [f32; 2],
+}
+
+pub struct Controls {
+    pub config: ControlsConfig,
+    pub actions: ActionState,
+}
+
+impl ControlsConfig {
+    pub fn load(filename: &str) -> Result<ControlsConfig, VoxIoError> {
+        read_config_from_file(filename)
+    }
+    pub fn load_or_default(filename: &str) -> ControlsConfig {
+        match ControlsConfig::load(filename) {
+            Ok(config) => config,
+            Err(VoxIoError::NotFound(_)) => {
+                println!("no controls found at {}, using the default controls", filename);
+                ControlsConfig::default()
+            }
+            Err(e) => {
+                println!("could not load controls: {}, using the default controls", e);
+                ControlsConfig::default()
+            }
+        }
+    }
+}
+
+impl Default for ControlsConfig {
+    fn default() -> Self {
+        let mut bindings = HashMap::new();
+        bindings.insert(Action::MoveForward, vec![Binding::Key(VirtualKeyCode::W)]);
+        bindings.insert(Action::MoveBack, vec![Binding::Key(VirtualKeyCode::S)]);
+        bindings.insert(Action::MoveLeft, vec![Binding::Key(VirtualKeyCode::A)]);
+        bindings.insert(Action::MoveRight, vec![Binding::Key(VirtualKeyCode::D)]);
+        bindings.insert(Action::Jump, vec![Binding::Key(VirtualKeyCode::Space)]);
+        bindings.insert(Action::Descend, vec![Binding::Key(VirtualKeyCode::LShift)]);
+        bindings.insert(Action::ToggleMovementMode, vec![Binding::Key(VirtualKeyCode::F)]);
+        bindings.insert(Action::Break, vec![Binding::Mouse(MouseButton::Left)]);
+        bindings.insert(Action::Place, vec![Binding::Mouse(MouseButton::Right)]);
+        bindings.insert(Action::Exit, vec![Binding::Key(VirtualKeyCode::Escape)]);
+        let slot_keys = [
+            VirtualKeyCode::Key1,
+            VirtualKeyCode::Key2,
+            VirtualKeyCode::Key3,
+            VirtualKeyCode::Key4,
+            VirtualKeyCode::Key5,
+            VirtualKeyCode::Key6,
+            VirtualKeyCode::Key7,
+            VirtualKeyCode::Key8,
+            VirtualKeyCode::Key9,
+        ];
+        for (slot, key) in slot_keys.iter().enumerate() {
+            bindings.insert(Action::SelectSlot(slot), vec![Binding::Key(*key)]);
+        }
+        ControlsConfig {
+            bindings,
+            mouse_sensitivity: 0.004,
+            invert_mouse_x: false,
+            invert_mouse_y: false,
+        }
+    }
+}
+
+impl ActionState {
+    pub fn held(&self, action: Action) -> bool {
+        self.held.contains(&action)
+    }
+    pub fn pressed(&self, action: Action) -> bool {
+        self.pressed.contains(&action)
+    }
+    // starts the next frame, pressed is only set for actions that were not held in the last one
+    pub fn set_held(&mut self, held: HashSet<Action>) {
+        self.pressed = held.difference(&self.held).copied().collect();
+        self.held = held;
+    }
+}
+
+impl Binding {
+    pub fn is_held(&self, input: &Input) -> bool {
+        match self {
+            Binding::Key(key) => input.key_pressed(*key),
+            Binding::Mouse(button) => {
+                let state = match button {
+                    MouseButton::Left => input.mouse_state.get_left_button(),
+                    MouseButton::Right => input.mouse_state.get_right_button(),
+                    MouseButton::Middle => input.mouse_state.get_middle_button(),
+                };
+                state == ButtonState::Pressed || state == ButtonState::Down
+            }
+        }
+    }
+}
+
+impl Controls {
+    pub fn new(config: ControlsConfig) -> Controls {
+        Controls {
+            config,
+            actions: ActionState::default(),
+        }
+    }
+    pub fn update(&mut self, input: &Input) {
+        let held = self
+            .config
+            .bindings
+            .iter()
+            .filter(|(_, bindings)| bindings.iter().any(|binding| binding.is_held(input)))
+            .map(|(action, _)| *action)
+            .collect();
+        self.actions.set_held(held);
+        let mouse_change = input.mouse_change();
+        let invert = |inverted: bool| if inverted { -1.0 } else { 1.0 };
+        self.actions.look = [
+            mouse_change[0] * self.config.mouse_sensitivity * invert(self.config.invert_mouse_x),
+            -mouse_change[1] * self.config.mouse_sensitivity * invert(self.config.invert_mouse_y),
+        ];
+    }
+}
@@ -1,7 +1,9 @@
 mod algorithms;
 pub mod blocks;
 pub mod chunk_render_data;
+pub mod controls;
+pub mod physics;
 pub mod player;
-mod structures;
+pub mod structures;
 pub mod world;
 pub mod world_gen;
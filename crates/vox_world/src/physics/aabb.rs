@@ -0,0 +1,63 @@
+use vox_core::positions::GlobalBlockPos;
+
+// boxes closer than this count as touching, keeps float errors from letting boxes sink into each other
+pub const CONTACT_EPSILON: f32 = 1e-4;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub min: [f32; 3],
+    pub max: [f32; 3],
+}
+
+impl Aabb {
+    pub fn new(min: [f32; 3], max: [f32; 3]) -> Aabb {
+        Aabb { min, max }
+    }
+    pub fn block(pos: &GlobalBlockPos) -> Aabb {
+        let min = [pos.x as f32, pos.y as f32, pos.z as f32];
+        return Aabb::new(min, [min[0] + 1.0, min[1] + 1.0, min[2] + 1.0]);
+    }
+    pub fn offset(&self, motion: [f32; 3]) -> Aabb {
+        Aabb::new(
+            [self.min[0] + motion[0], self.min[1] + motion[1], self.min[2] + motion[2]],
+            [self.max[0] + motion[0], self.max[1] + motion[1], self.max[2] + motion[2]],
+        )
+    }
+    pub fn offset_axis(&self, axis: usize, amount: f32) -> Aabb {
+        let mut motion = [0.0; 3];
+        motion[axis] = amount;
+        return self.offset(motion);
+    }
+    // the region the box passes through while moving by motion
+    pub fn expand_towards(&self, motion: [f32; 3]) -> Aabb {
+        let mut expanded = *self;
+        for axis in 0..3 {
+            if motion[axis] < 0.0 {
+                expanded.min[axis] += motion[axis];
+            } else {
+                expanded.max[axis] += motion[axis];
+            }
+        }
+        return expanded;
+    }
+    pub fn intersects(&self, other: &Aabb) -> bool {
+        (0..3).all(|axis| self.overlaps_on_axis(other, axis))
+    }
+    pub fn overlaps_on_axis(&self, other: &Aabb, axis: usize) -> bool {
+        self.min[axis] < other.max[axis] - CONTACT_EPSILON && self.max[axis] > other.min[axis] + CONTACT_EPSILON
+    }
+    // shortens motion along axis so this box stops at other, boxes that already overlap are ignored
+    pub fn clip_axis(&self, other: &Aabb, axis: usize, motion: f32) -> f32 {
+        let others_overlap = (0..3).filter(|a| *a != axis).all(|a| self.overlaps_on_axis(other, a));
+        if !others_overlap {
+            return motion;
+        }
+        if motion > 0.0 && other.min[axis] >= self.max[axis] - CONTACT_EPSILON {
+            return motion.min(other.min[axis] - self.max[axis]).max(0.0);
+        }
+        if motion < 0.0 && other.max[axis] <= self.min[axis] + CONTACT_EPSILON {
+            return motion.max(other.max[axis] - self.min[axis]).min(0.0);
+        }
+        return motion;
+    }
+}
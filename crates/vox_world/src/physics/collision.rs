@@ -0,0 +1,110 @@
+use crate::blocks::block::{get_blockid, is_solid};
+use crate::blocks::block_type::BlockType;
+use crate::physics::aabb::Aabb;
+use crate::world::small_world::SmallWorld;
+use vox_core::constants::{CHUNKSIZE, METACHUNKSIZE};
+use vox_core::positions::GlobalBlockPos;
+
+// the highest ledge the player walks onto without jumping
+pub const STEP_HEIGHT: f32 = 1.0;
+// how far below the box is checked for ground before stepping up
+const GROUND_PROBE: f32 = 0.01;
+
+// axes are resolved in this order, vertical first so walking over the ground does not catch on it
+const AXIS_ORDER: [usize; 3] = [1, 0, 2];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CollisionResult {
+    // the motion that was actually applied
+    pub motion: [f32; 3],
+    // the axes along which the motion was cut short
+    pub collided: [bool; 3],
+    pub on_ground: bool,
+    pub stepped_up: bool,
+}
+
+// solid blocks block movement, so do blocks below the world and in metachunks that are not loaded yet,
+// the player would otherwise fall through the world while it is loading
+pub fn blocks_movement(world: &SmallWorld, pos: &GlobalBlockPos) -> bool {
+    if pos.y < 0 {
+        return true;
+    }
+    if pos.y >= (CHUNKSIZE * METACHUNKSIZE) as i32 {
+        return false;
+    }
+    let block = world.get_block(*pos);
+    return is_solid(block) || block == get_blockid(BlockType::Unknown);
+}
+
+pub fn solid_boxes(world: &SmallWorld, region: &Aabb) -> Vec<Aabb> {
+    let mut boxes = Vec::new();
+    let min: Vec<i32> = region.min.iter().map(|v| v.floor() as i32).collect();
+    let max: Vec<i32> = region.max.iter().map(|v| v.ceil() as i32).collect();
+    for x in min[0]..max[0] {
+        for y in min[1]..max[1] {
+            for z in min[2]..max[2] {
+                let pos = GlobalBlockPos { x, y, z };
+                if blocks_movement(world, &pos) {
+                    boxes.push(Aabb::block(&pos));
+                }
+            }
+        }
+    }
+    return boxes;
+}
+
+pub fn overlaps_solid(world: &SmallWorld, aabb: &Aabb) -> bool {
+    solid_boxes(world, aabb).iter().any(|b| b.intersects(aabb))
+}
+
+// moves the box one axis at a time and stops it at the first solid block along each axis, the
+// remaining axes keep their motion so the box slides along walls
+pub fn sweep(world: &SmallWorld, aabb: &Aabb, motion: [f32; 3]) -> CollisionResult {
+    let boxes = solid_boxes(world, &aabb.expand_towards(motion));
+    let mut moved = *aabb;
+    let mut result = CollisionResult {
+        motion: [0.0; 3],
+        collided: [false; 3],
+        on_ground: false,
+        stepped_up: false,
+    };
+    for axis in AXIS_ORDER {
+        let mut clipped = motion[axis];
+        for b in boxes.iter() {
+            clipped = moved.clip_axis(b, axis, clipped);
+        }
+        moved = moved.offset_axis(axis, clipped);
+        result.motion[axis] = clipped;
+        result.collided[axis] = clipped != motion[axis];
+    }
+    result.on_ground = result.collided[1] && motion[1] < 0.0;
+    return result;
+}
+
+// sweep, but a box standing on the ground that walks into a ledge of at most step_height is lifted onto it
+pub fn move_and_slide(world: &SmallWorld, aabb: &Aabb, motion: [f32; 3], step_height: f32) -> CollisionResult {
+    let result = sweep(world, aabb, motion);
+    let blocked = result.collided[0] || result.collided[2];
+    if step_height <= 0.0 || !blocked || motion[1] > 0.0 {
+        return result;
+    }
+    let grounded = result.on_ground || sweep(world, aabb, [0.0, -GROUND_PROBE, 0.0]).collided[1];
+    if !grounded {
+        return result;
+    }
+    let up = sweep(world, aabb, [0.0, step_height, 0.0]).motion[1];
+    let raised = aabb.offset([0.0, up, 0.0]);
+    let across = sweep(world, &raised, [motion[0], 0.0, motion[2]]);
+    let moved = raised.offset(across.motion);
+    let down = sweep(world, &moved, [0.0, -up + motion[1].min(0.0), 0.0]);
+    let horizontal = |m: [f32; 3]| m[0] * m[0] + m[2] * m[2];
+    if horizontal(across.motion) <= horizontal(result.motion) {
+        return result;
+    }
+    return CollisionResult {
+        motion: [across.motion[0], up + down.motion[1], across.motion[2]],
+        collided: [across.collided[0], down.collided[1], across.collided[2]],
+        on_ground: down.collided[1],
+        stepped_up: true,
+    };
+}
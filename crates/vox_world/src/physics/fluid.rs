@@ -0,0 +1,42 @@
+use crate::blocks::block::is_fluid;
+use crate::physics::aabb::Aabb;
+use crate::world::small_world::SmallWorld;
+use vox_core::positions::GlobalBlockPos;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct FluidState {
+    // part of the box height that is in fluid, from 0 to 1, the deepest column of the box counts
+    pub submerged: f32,
+    // used by the renderer for the underwater tint and fog
+    pub eyes_underwater: bool,
+}
+
+impl FluidState {
+    pub fn new(world: &SmallWorld, aabb: &Aabb, eye: [f32; 3]) -> FluidState {
+        let height = aabb.max[1] - aabb.min[1];
+        let mut deepest = 0.0f32;
+        for x in aabb.min[0].floor() as i32..aabb.max[0].ceil() as i32 {
+            for z in aabb.min[2].floor() as i32..aabb.max[2].ceil() as i32 {
+                let mut depth = 0.0;
+                for y in aabb.min[1].floor() as i32..aabb.max[1].ceil() as i32 {
+                    if is_fluid(world.get_block(GlobalBlockPos { x, y, z })) {
+                        depth += (aabb.max[1].min(y as f32 + 1.0) - aabb.min[1].max(y as f32)).max(0.0);
+                    }
+                }
+                deepest = deepest.max(depth);
+            }
+        }
+        let eye_block = GlobalBlockPos {
+            x: eye[0].floor() as i32,
+            y: eye[1].floor() as i32,
+            z: eye[2].floor() as i32,
+        };
+        return FluidState {
+            submerged: (deepest / height).min(1.0),
+            eyes_underwater: is_fluid(world.get_block(eye_block)),
+        };
+    }
+    pub fn in_fluid(&self) -> bool {
+        self.submerged > 0.0
+    }
+}
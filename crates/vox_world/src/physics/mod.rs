@@ -0,0 +1,5 @@
+pub mod aabb;
+pub mod collision;
+pub mod fluid;
+pub mod movement;
+pub mod raycast;
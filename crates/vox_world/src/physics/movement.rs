@@ -0,0 +1,227 @@
+use crate::physics::aabb::Aabb;
+use crate::physics::collision::{move_and_slide, CollisionResult, STEP_HEIGHT};
+use crate::physics::fluid::FluidState;
+use crate::world::small_world::SmallWorld;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MovementMode {
+    Walk,
+    Fly,
+    // flying through blocks
+    Noclip,
+}
+
+// speeds are in blocks per second, accelerations in blocks per second squared
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MovementConfig {
+    pub walk_speed: f32,
+    pub fly_speed: f32,
+    pub ground_acceleration: f32,
+    pub air_acceleration: f32,
+    pub fly_acceleration: f32,
+    // deceleration when there is no input
+    pub ground_friction: f32,
+    pub air_friction: f32,
+    pub gravity: f32,
+    pub terminal_velocity: f32,
+    pub jump_speed: f32,
+    // a jump still works this long after walking off a ledge
+    pub coyote_time: f32,
+    pub swim_speed: f32,
+    pub swim_up_speed: f32,
+    pub swim_acceleration: f32,
+    // gravity in fluid, pushed against by the buoyancy of the submerged part of the box
+    pub fluid_gravity: f32,
+    pub buoyancy: f32,
+    // part of the velocity lost per second in fluid
+    pub fluid_drag: f32,
+}
+
+// what the player wants to do this tick, the horizontal part of wish is in world space with a length
+// of at most 1, the vertical part is only used while flying
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct MovementInput {
+    pub wish: [f32; 3],
+    pub jump: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MovementController {
+    pub mode: MovementMode,
+    pub config: MovementConfig,
+    pub velocity: [f32; 3],
+    pub on_ground: bool,
+    // time left to jump after leaving the ground
+    pub coyote_timer: f32,
+}
+
+impl MovementMode {
+    pub fn next(&self) -> MovementMode {
+        match self {
+            MovementMode::Walk => MovementMode::Fly,
+            MovementMode::Fly => MovementMode::Noclip,
+            MovementMode::Noclip => MovementMode::Walk,
+        }
+    }
+}
+
+impl Default for MovementConfig {
+    fn default() -> Self {
+        MovementConfig {
+            walk_speed: 4.5,
+            fly_speed: 20.0,
+            ground_acceleration: 50.0,
+            air_acceleration: 12.0,
+            fly_acceleration: 60.0,
+            ground_friction: 40.0,
+            air_friction: 2.0,
+            gravity: 28.0,
+            terminal_velocity: 60.0,
+            // high enough to jump onto a single block
+            jump_speed: 8.5,
+            coyote_time: 0.1,
+            swim_speed: 2.5,
+            swim_up_speed: 3.0,
+            swim_acceleration: 15.0,
+            fluid_gravity: 12.0,
+            // floats with the eyes just above the surface
+            buoyancy: 16.0,
+            fluid_drag: 2.0,
+        }
+    }
+}
+
+impl MovementController {
+    pub fn new(mode: MovementMode) -> MovementController {
+        MovementController {
+            mode,
+            config: MovementConfig::default(),
+            velocity: [0.0; 3],
+            on_ground: false,
+            coyote_timer: 0.0,
+        }
+    }
+    pub fn set_mode(&mut self, mode: MovementMode) {
+        self.mode = mode;
+        self.velocity = [0.0; 3];
+        self.on_ground = false;
+        self.coyote_timer = 0.0;
+    }
+    // advances the movement by dt and returns how far the box moved
+    pub fn update(
+        &mut self,
+        aabb: &Aabb,
+        input: &MovementInput,
+        fluid: &FluidState,
+        dt: f32,
+        world: &SmallWorld,
+    ) -> [f32; 3] {
+        return match self.mode {
+            MovementMode::Walk if fluid.in_fluid() => self.swim(aabb, input, fluid, dt, world),
+            MovementMode::Walk => self.walk(aabb, input, dt, world),
+            MovementMode::Fly => self.fly(aabb, input, dt, Some(world)),
+            MovementMode::Noclip => self.fly(aabb, input, dt, None),
+        };
+    }
+    fn walk(&mut self, aabb: &Aabb, input: &MovementInput, dt: f32, world: &SmallWorld) -> [f32; 3] {
+        let config = self.config;
+        let wants_to_move = input.wish[0] != 0.0 || input.wish[2] != 0.0;
+        let rate = match (self.on_ground, wants_to_move) {
+            (true, true) => config.ground_acceleration,
+            (true, false) => config.ground_friction,
+            (false, true) => config.air_acceleration,
+            (false, false) => config.air_friction,
+        };
+        let target = [input.wish[0] * config.walk_speed, input.wish[2] * config.walk_speed];
+        let mut horizontal = [self.velocity[0], self.velocity[2]];
+        approach(&mut horizontal, &target, rate * dt);
+        self.velocity[0] = horizontal[0];
+        self.velocity[2] = horizontal[1];
+
+        if self.on_ground {
+            self.coyote_timer = config.coyote_time;
+        } else {
+            self.coyote_timer -= dt;
+        }
+        if input.jump && self.coyote_timer > 0.0 && self.velocity[1] <= 0.0 {
+            self.velocity[1] = config.jump_speed;
+            self.coyote_timer = 0.0;
+        }
+        self.velocity[1] = (self.velocity[1] - config.gravity * dt).max(-config.terminal_velocity);
+
+        let motion = [self.velocity[0] * dt, self.velocity[1] * dt, self.velocity[2] * dt];
+        let result = move_and_slide(world, aabb, motion, STEP_HEIGHT);
+        self.stop_on_collision(&result);
+        self.on_ground = result.on_ground || result.stepped_up;
+        return result.motion;
+    }
+    // jumping swims up, without input the buoyancy keeps the player floating at the surface
+    fn swim(&mut self, aabb: &Aabb, input: &MovementInput, fluid: &FluidState, dt: f32, world: &SmallWorld) -> [f32; 3] {
+        let config = self.config;
+        let target = [input.wish[0] * config.swim_speed, input.wish[2] * config.swim_speed];
+        let mut horizontal = [self.velocity[0], self.velocity[2]];
+        approach(&mut horizontal, &target, config.swim_acceleration * dt);
+        self.velocity[0] = horizontal[0];
+        self.velocity[2] = horizontal[1];
+
+        self.velocity[1] += (fluid.submerged * config.buoyancy - config.fluid_gravity) * dt;
+        if input.jump && self.velocity[1] < config.swim_up_speed {
+            self.velocity[1] = (self.velocity[1] + config.swim_acceleration * dt).min(config.swim_up_speed);
+        }
+        let drag = (1.0 - config.fluid_drag * dt).max(0.0);
+        self.velocity = self.velocity.map(|v| v * drag);
+        self.coyote_timer = 0.0;
+
+        let motion = [self.velocity[0] * dt, self.velocity[1] * dt, self.velocity[2] * dt];
+        let result = move_and_slide(world, aabb, motion, STEP_HEIGHT);
+        self.stop_on_collision(&result);
+        self.on_ground = result.on_ground || result.stepped_up;
+        return result.motion;
+    }
+    // without a world nothing collides
+    fn fly(&mut self, aabb: &Aabb, input: &MovementInput, dt: f32, world: Option<&SmallWorld>) -> [f32; 3] {
+        let config = self.config;
+        let wants_to_move = input.wish.iter().any(|v| *v != 0.0);
+        let rate = if wants_to_move {
+            config.fly_acceleration
+        } else {
+            config.ground_friction
+        };
+        let target = input.wish.map(|v| v * config.fly_speed);
+        approach(&mut self.velocity, &target, rate * dt);
+        self.on_ground = false;
+        let motion = [self.velocity[0] * dt, self.velocity[1] * dt, self.velocity[2] * dt];
+        return match world {
+            Some(world) => {
+                let result = move_and_slide(world, aabb, motion, 0.0);
+                self.stop_on_collision(&result);
+                result.motion
+            }
+            None => motion,
+        };
+    }
+    fn stop_on_collision(&mut self, result: &CollisionResult) {
+        for axis in 0..3 {
+            if result.collided[axis] {
+                self.velocity[axis] = 0.0;
+            }
+        }
+    }
+}
+
+// moves a velocity towards target by at most max_change
+fn approach(current: &mut [f32], target: &[f32], max_change: f32) {
+    let length = current
+        .iter()
+        .zip(target)
+        .map(|(c, t)| (t - c) * (t - c))
+        .sum::<f32>()
+        .sqrt();
+    for (c, t) in current.iter_mut().zip(target) {
+        if length <= max_change {
+            *c = *t;
+        } else {
+            *c += (t - *c) / length * max_change;
+        }
+    }
+}
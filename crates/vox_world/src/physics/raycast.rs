@@ -0,0 +1,97 @@
+use crate::blocks::block::BlockId;
+use crate::world::small_world::SmallWorld;
+use vox_core::constants::{CHUNKSIZE, METACHUNKSIZE};
+use vox_core::positions::{GlobalBlockPos, ObjectPos};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RaycastHit {
+    pub block: GlobalBlockPos,
+    pub block_id: BlockId,
+    // points out of the face the ray entered through, all zero when the ray starts inside the hit block
+    pub normal: [i32; 3],
+    // distance along the ray to where it enters the hit block
+    pub distance: f32,
+    // the block the ray passed through right before the hit, the hit block itself when the ray starts in it
+    pub previous: GlobalBlockPos,
+}
+
+// walks every block the ray passes through in order (amanatides and woo) until filter accepts one
+pub fn raycast(
+    world: &SmallWorld,
+    origin: ObjectPos,
+    dir: [f32; 3],
+    max_dist: f32,
+    filter: impl Fn(BlockId) -> bool,
+) -> Option<RaycastHit> {
+    let length = (dir[0] * dir[0] + dir[1] * dir[1] + dir[2] * dir[2]).sqrt();
+    if length == 0.0 || !length.is_finite() {
+        return None;
+    }
+    let dir = dir.map(|v| v / length);
+    let origin = [origin.x, origin.y, origin.z];
+    let mut block = origin.map(|v| v.floor() as i32);
+    let mut step = [0i32; 3];
+    // distance along the ray to the next block boundary on every axis, and between boundaries
+    let mut t_max = [f32::INFINITY; 3];
+    let mut t_delta = [f32::INFINITY; 3];
+    for axis in 0..3 {
+        if dir[axis] > 0.0 {
+            step[axis] = 1;
+            t_max[axis] = (block[axis] as f32 + 1.0 - origin[axis]) / dir[axis];
+            t_delta[axis] = 1.0 / dir[axis];
+        } else if dir[axis] < 0.0 {
+            step[axis] = -1;
+            t_max[axis] = (origin[axis] - block[axis] as f32) / -dir[axis];
+            t_delta[axis] = 1.0 / -dir[axis];
+        }
+    }
+    let to_pos = |block: [i32; 3]| GlobalBlockPos {
+        x: block[0],
+        y: block[1],
+        z: block[2],
+    };
+    let start = to_pos(block);
+    let block_id = world.get_block(start);
+    if filter(block_id) {
+        return Some(RaycastHit {
+            block: start,
+            block_id,
+            normal: [0; 3],
+            distance: 0.0,
+            previous: start,
+        });
+    }
+    let world_height = (CHUNKSIZE * METACHUNKSIZE) as i32;
+    loop {
+        let mut axis = 0;
+        for a in 1..3 {
+            if t_max[a] < t_max[axis] {
+                axis = a;
+            }
+        }
+        let distance = t_max[axis];
+        if distance > max_dist {
+            return None;
+        }
+        let previous = to_pos(block);
+        block[axis] += step[axis];
+        t_max[axis] += t_delta[axis];
+        // nothing to hit once the ray has left the world vertically
+        if (block[1] < 0 && step[1] <= 0) || (block[1] >= world_height && step[1] >= 0) {
+            return None;
+        }
+        let pos = to_pos(block);
+        let block_id = world.get_block(pos);
+        if filter(block_id) {
+            let mut normal = [0; 3];
+            normal[axis] = -step[axis];
+            return Some(RaycastHit {
+                block: pos,
+                block_id,
+                normal,
+                distance,
+                previous,
+            });
+        }
+    }
+}
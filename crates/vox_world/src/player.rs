@@ -1,21 +1,51 @@
+use crate::blocks::block::{get_blockid, is_fluid, is_solid, BlockId};
+use crate::blocks::block_type::BlockType;
+use crate::controls::{Action, ActionState};
+use crate::physics::aabb::Aabb;
+use crate::physics::collision::overlaps_solid;
+use crate::physics::fluid::FluidState;
+use crate::physics::movement::{MovementController, MovementInput, MovementMode};
+use crate::physics::raycast::RaycastHit;
 use crate::world::small_world::SmallWorld;
-use nalgebra::{Matrix3, Vector3};
 use std::f32::consts::PI;
-use vox_core::constants::COLORS;
-use vox_core::positions::{ChunkPos, ObjectPos};
-use vox_core::utils::{get_rotation_matrix_y, get_rotation_matrix_z};
-use winit::event::VirtualKeyCode;
-use winit_window_control::input::input::Input;
+use vox_core::constants::{CHUNKSIZE, METACHUNKSIZE};
+use vox_core::positions::{ChunkPos, GlobalBlockPos, ObjectPos};
+use vox_render::renderer::camera::Camera;
 
+pub const PLAYER_WIDTH: f32 = 0.6;
+pub const PLAYER_HEIGHT: f32 = 1.8;
+// the player position is the eye position, this far above the bottom of the box
+pub const EYE_HEIGHT: f32 = 1.6;
+// how far away blocks can be broken and placed
+pub const REACH_DISTANCE: f32 = 5.0;
+// seconds between edits while a mouse button is held
+pub const EDIT_COOLDOWN: f32 = 0.25;
+// blocks that can be selected with the SelectSlot actions
+pub const HOTBAR: [BlockType; 9] = [
+    BlockType::Stone,
+    BlockType::Dirt,
+    BlockType::Grass,
+    BlockType::Sand,
+    BlockType::Log,
+    BlockType::BirchLog,
+    BlockType::Leaf,
+    BlockType::Water,
+    BlockType::CoalOre,
+];
 pub struct Player {
     pub position: ObjectPos,
-    pub direction: Vector3<f32>,
-    pub up: [f32; 3],
-    pub speed: f32,
-    pub camera_speed: f32,
+    pub camera: Camera,
     pub render_distance: f32,
     pub generated_chunks_for: ChunkPos,
-    pub gravity: f32,
+    pub movement: MovementController,
+    // the input of the last frame, applied on the next update
+    pub movement_input: MovementInput,
+    pub fluid: FluidState,
+    pub selected_block: BlockId,
+    pub break_held: bool,
+    pub place_held: bool,
+    // time until the next edit is allowed
+    pub edit_cooldown: f32,
 }
 
 impl Player {
@@ -26,176 +56,168 @@ impl Player {
                 y: 0.0,
                 z: 0.0,
             },
-            direction: Vector3::new(0f32, 0.0f32, 1.0f32),
-            up: [0f32, 1.0f32, 0f32],
-            speed: 100f32,
-            camera_speed: 0.5f32,
+            camera: Camera::new(0.0, 0.0),
             render_distance: 5000f32,
             generated_chunks_for: ChunkPos {
                 x: i32::max_value(),
                 y: i32::max_value(),
                 z: i32::max_value(),
             },
-            gravity: 0.0,
+            movement: MovementController::new(MovementMode::Walk),
+            movement_input: MovementInput::default(),
+            fluid: FluidState::default(),
+            selected_block: get_blockid(HOTBAR[0]),
+            break_held: false,
+            place_held: false,
+            edit_cooldown: 0.0,
         }
     }
 
-    pub fn handle_input(&mut self, input: &Input, dt: &f32, world: &SmallWorld) {
-        self.change_position(
-            input,
-            VirtualKeyCode::A,
-            1.5f32 * PI,
-            *dt * self.speed,
-            world,
-        );
-        self.change_position(
-            input,
-            VirtualKeyCode::D,
-            0.5f32 * PI,
-            *dt * self.speed,
-            world,
-        );
-        self.change_position(input, VirtualKeyCode::W, 0f32 * PI, *dt * self.speed, world);
-        self.change_position(input, VirtualKeyCode::S, 1f32 * PI, *dt * self.speed, world);
-        if input.key_pressed(VirtualKeyCode::Space) {
-            let diff = *dt * self.speed;
-            if !Player::collides(&self.position.get_diff(0.0, diff, 0.0), world) {
-                self.position.y += diff;
-            }
+    pub fn handle_input(&mut self, actions: &ActionState) {
+        let mut wish = [0.0f32; 3];
+        self.add_wish(actions, Action::MoveLeft, 1.5f32 * PI, &mut wish);
+        self.add_wish(actions, Action::MoveRight, 0.5f32 * PI, &mut wish);
+        self.add_wish(actions, Action::MoveForward, 0f32 * PI, &mut wish);
+        self.add_wish(actions, Action::MoveBack, 1f32 * PI, &mut wish);
+        let length = (wish[0] * wish[0] + wish[2] * wish[2]).sqrt();
+        if length > 1.0 {
+            wish[0] /= length;
+            wish[2] /= length;
+        }
+        if actions.held(Action::Jump) {
+            wish[1] += 1.0;
+        }
+        if actions.held(Action::Descend) {
+            wish[1] -= 1.0;
         }
-        if input.key_pressed(VirtualKeyCode::LShift) {
-            let diff = -*dt * self.speed;
-            if !Player::collides(&self.position.get_diff(0.0, diff, 0.0), world) {
-                self.position.y += diff;
+        self.movement_input = MovementInput {
+            wish,
+            jump: actions.held(Action::Jump),
+        };
+
+        if actions.pressed(Action::ToggleMovementMode) {
+            self.movement.set_mode(self.movement.mode.next());
+            println!("movement mode: {:?}", self.movement.mode);
+        }
+        for (slot, block) in HOTBAR.iter().enumerate() {
+            if actions.pressed(Action::SelectSlot(slot)) {
+                self.selected_block = get_blockid(*block);
             }
         }
+        self.break_held = actions.held(Action::Break);
+        self.place_held = actions.held(Action::Place);
 
-        let mouse_change = input.mouse_change();
-        let xdiff = mouse_change[0] * dt * self.camera_speed;
-        let ydiff = -mouse_change[1] * dt * self.camera_speed;
+        self.camera.rotate(actions.look[0], actions.look[1]);
+    }
 
-        if ydiff < 0f32 {
-            self.change_direction_vertical(ydiff);
-        } else {
-            self.change_direction_vertical(ydiff);
-        }
-        if xdiff < 0f32 {
-            self.change_direction_horizontal(&get_rotation_matrix_y(xdiff));
-        } else {
-            self.change_direction_horizontal(&get_rotation_matrix_y(xdiff));
+    // adds the horizontal direction of action, rotated away from the view direction, to wish
+    pub fn add_wish(&self, actions: &ActionState, action: Action, rotation: f32, wish: &mut [f32; 3]) {
+        if actions.held(action) {
+            let angle = self.camera.yaw + rotation;
+            wish[0] += angle.sin();
+            wish[2] += angle.cos();
         }
     }
+    pub fn direction(&self) -> [f32; 3] {
+        self.camera.direction()
+    }
 
-    pub fn change_position(
-        &mut self,
-        input: &Input,
-        key: VirtualKeyCode,
-        rotation_degree: f32,
-        change: f32,
-        world: &SmallWorld,
-    ) {
-        if input.key_pressed(key) {
-            let move_vec = get_rotation_matrix_y(rotation_degree) * &self.direction;
-            let to_extend =
-                1f32 / (move_vec[0].powf(2f32).abs() + move_vec[2].powf(2f32).abs()).sqrt();
-            let x_change = change * move_vec.x * to_extend;
-            let z_change = change * move_vec.z * to_extend;
-            if !Player::collides(&self.position.get_diff(x_change, 0.0, z_change), world) {
-                self.position.x += x_change;
-                self.position.z += z_change;
+    pub fn update(&mut self, dt: &f32, world: &SmallWorld) {
+        let aabb = self.get_aabb();
+        let eye = [self.position.x, self.position.y, self.position.z];
+        self.fluid = FluidState::new(world, &aabb, eye);
+        let motion = self.movement.update(&aabb, &self.movement_input, &self.fluid, *dt, world);
+        self.position = self.position.get_diff(motion[0], motion[1], motion[2]);
+        if self.movement.mode == MovementMode::Noclip {
+            return;
+        }
+        // a block placed in the player or a spawn inside terrain leaves the box stuck, it is moved to
+        // the first free space above, blocks of metachunks that are still loading are waited out instead
+        if world.get_meta_chunk(&self.position.get_meta_chunk()).is_none() || !Player::collides(&self.position, world) {
+            return;
+        }
+        let world_height = (CHUNKSIZE * METACHUNKSIZE) as i32;
+        let feet = (self.position.y - EYE_HEIGHT).floor() as i32;
+        for y in (feet + 1)..=world_height {
+            let pos = ObjectPos {
+                x: self.position.x,
+                y: y as f32 + EYE_HEIGHT,
+                z: self.position.z,
+            };
+            if !Player::collides(&pos, world) {
+                self.position = pos;
+                return;
             }
         }
     }
-    pub fn change_direction_horizontal(&mut self, mat: &Matrix3<f32>) {
-        self.direction = mat * &self.direction;
-    }
-    pub fn change_direction_vertical(&mut self, change: f32) {
-        let mut backup_dir = self.direction.clone();
-        backup_dir[1] = 0f32;
-        let angle = (backup_dir[2] / backup_dir[0]).atan();
-        if backup_dir[0].is_sign_negative() {
-            self.direction = get_rotation_matrix_y(-angle)
-                * get_rotation_matrix_z(-change)
-                * get_rotation_matrix_y(angle)
-                * &self.direction;
+    // breaks or places a block when a mouse button is held and the cooldown has passed, returns the
+    // changed block
+    pub fn handle_edits(&mut self, dt: &f32, world: &mut SmallWorld) -> Option<GlobalBlockPos> {
+        self.edit_cooldown = (self.edit_cooldown - dt).max(0.0);
+        if self.edit_cooldown > 0.0 {
+            return None;
+        }
+        let edited = if self.break_held {
+            self.break_block(world)
+        } else if self.place_held {
+            self.place_block(world, self.selected_block)
         } else {
-            self.direction = get_rotation_matrix_y(-angle)
-                * get_rotation_matrix_z(change)
-                * get_rotation_matrix_y(angle)
-                * &self.direction;
+            None
+        };
+        if edited.is_some() {
+            self.edit_cooldown = EDIT_COOLDOWN;
         }
-        if backup_dir[0].is_sign_positive() != self.direction[0].is_sign_positive() {
-            self.direction[0] = backup_dir[0];
+        return edited;
+    }
+    // the solid block the player is looking at within reach
+    pub fn target_block(&self, world: &SmallWorld) -> Option<RaycastHit> {
+        let eye = ObjectPos {
+            x: self.position.x,
+            y: self.position.y,
+            z: self.position.z,
+        };
+        world.raycast(eye, self.direction(), REACH_DISTANCE, is_solid)
+    }
+    pub fn break_block(&self, world: &mut SmallWorld) -> Option<GlobalBlockPos> {
+        let hit = self.target_block(world)?;
+        world.set_block(get_blockid(BlockType::Air), hit.block);
+        return Some(hit.block);
+    }
+    // places block against the targeted face, as long as that does not put it inside the player
+    pub fn place_block(&self, world: &mut SmallWorld, block: BlockId) -> Option<GlobalBlockPos> {
+        let hit = self.target_block(world)?;
+        let pos = hit.previous;
+        if pos == hit.block {
+            return None;
         }
-        if backup_dir[2].is_sign_positive() != self.direction[2].is_sign_positive() {
-            self.direction[2] = backup_dir[2];
+        let replaced = world.get_block(pos);
+        if replaced != get_blockid(BlockType::Air) && !is_fluid(replaced) {
+            return None;
         }
-    }
-
-    pub fn update(&mut self, _dt: &f32, world: &SmallWorld) {
-        loop {
-            if Player::collides(&self.position, world) {
-                self.position.y += 1.0;
-            } else {
-                return;
-            }
+        if is_solid(block) && Aabb::block(&pos).intersects(&self.get_aabb()) {
+            return None;
         }
+        world.set_block(block, pos);
+        return Some(pos);
+    }
+    pub fn get_aabb(&self) -> Aabb {
+        Player::aabb_at(&self.position)
+    }
+    // the box of a player whose eyes are at pos
+    pub fn aabb_at(pos: &ObjectPos) -> Aabb {
+        let half_width = PLAYER_WIDTH / 2.0;
+        let feet = pos.y - EYE_HEIGHT;
+        return Aabb::new(
+            [pos.x - half_width, feet, pos.z - half_width],
+            [pos.x + half_width, feet + PLAYER_HEIGHT, pos.z + half_width],
+        );
     }
-    //pub fn get_collision_points() -> [ObjectPos; 8] {}
     pub fn collides(pos: &ObjectPos, world: &SmallWorld) -> bool {
-        /*let blockpos = pos.get_block();
-        let faceblock = world.get_block(blockpos);
-        let feetblock = world.get_block(blockpos.get_diff(0, -1, 0));
-        return if (faceblock.is_some() && COLORS[faceblock.unwrap() as usize][3] == 255.0)
-            || (feetblock.is_some() && COLORS[feetblock.unwrap() as usize][3] == 255.0)
-        {
-            true
-        } else {
-            false
-        };*/
-        return false
+        overlaps_solid(world, &Player::aabb_at(pos))
     }
 
     pub fn get_view_matrix(&self) -> [[f32; 4]; 4] {
-        let f = {
-            let f = self.direction;
-            let len = f[0] * f[0] + f[1] * f[1] + f[2] * f[2];
-            let len = len.sqrt();
-            [f[0] / len, f[1] / len, f[2] / len]
-        };
-
-        let s = [
-            self.up[1] * f[2] - self.up[2] * f[1],
-            self.up[2] * f[0] - self.up[0] * f[2],
-            self.up[0] * f[1] - self.up[1] * f[0],
-        ];
-
-        let s_norm = {
-            let len = s[0] * s[0] + s[1] * s[1] + s[2] * s[2];
-            let len = len.sqrt();
-            [s[0] / len, s[1] / len, s[2] / len]
-        };
-
-        let u = [
-            f[1] * s_norm[2] - f[2] * s_norm[1],
-            f[2] * s_norm[0] - f[0] * s_norm[2],
-            f[0] * s_norm[1] - f[1] * s_norm[0],
-        ];
-
-        let p = [
-            -self.position.x * s_norm[0]
-                - self.position.y * s_norm[1]
-                - self.position.z * s_norm[2],
-            -self.position.x * u[0] - self.position.y * u[1] - self.position.z * u[2],
-            -self.position.x * f[0] - self.position.y * f[1] - self.position.z * f[2],
-        ];
-
-        [
-            [s_norm[0], u[0], f[0], 0.0],
-            [s_norm[1], u[1], f[1], 0.0],
-            [s_norm[2], u[2], f[2], 0.0],
-            [p[0], p[1], p[2], 1.0],
-        ]
+        self.camera.view_matrix([self.position.x, self.position.y, self.position.z])
     }
     pub fn chunk_in_view_distance(&self, pos: &ChunkPos) -> bool {
         self.position.get_chunk().get_distance(pos) < self.render_distance
@@ -1,2 +1,5 @@
+pub mod schematic;
 pub mod square;
+pub mod template;
 pub mod tree;
+pub mod vox_model;
@@ -0,0 +1,211 @@
+use crate::blocks::block::get_blocktype;
+use crate::blocks::block_type::BlockType;
+use crate::structures::template::{Placement, StructureTemplate, TemplateBlock};
+use crate::world::small_world::SmallWorld;
+use crate::world_gen::basic::ChunkGenerator;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use vox_core::positions::GlobalBlockPos;
+use vox_io::io::config_reader::read_config_from_file;
+use vox_io::io::error::VoxIoError;
+use vox_io::io::sponge_schematic::{read_schematic_from_file, write_schematic_to_file, SpongeSchematic};
+
+pub const BLOCK_MAPPING_FILE: &str = "structures/block_names.ron";
+// minecraft 1.18.2, the data version written into exported schematics
+const DATA_VERSION: i32 = 2975;
+
+// translates between minecraft block names and our blocks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockNameMapping {
+    // names without block state properties, "minecraft:oak_log" also matches "minecraft:oak_log[axis=x]"
+    pub import: HashMap<String, TemplateBlock>,
+    // used for names that are not in the import table
+    pub unknown: TemplateBlock,
+    pub export: Vec<(BlockType, String)>,
+}
+
+impl BlockNameMapping {
+    pub fn load_or_default(filename: &str) -> BlockNameMapping {
+        match read_config_from_file(filename) {
+            Ok(mapping) => mapping,
+            Err(VoxIoError::NotFound(_)) => BlockNameMapping::default(),
+            Err(e) => {
+                println!("could not load block name mapping: {}, using the default mapping", e);
+                BlockNameMapping::default()
+            }
+        }
+    }
+    pub fn to_block(&self, name: &str) -> TemplateBlock {
+        if let Some(block) = self.import.get(name) {
+            return *block;
+        }
+        let base = name.split('[').next().unwrap_or(name);
+        return match self.import.get(base) {
+            Some(block) => *block,
+            None => self.unknown,
+        };
+    }
+    pub fn to_name(&self, block: BlockType) -> String {
+        match self.export.iter().find(|(b, _)| *b == block) {
+            Some((_, name)) => name.clone(),
+            None => "minecraft:stone".to_string(),
+        }
+    }
+}
+
+impl Default for BlockNameMapping {
+    fn default() -> Self {
+        let import = [
+            ("minecraft:air", BlockType::Air),
+            ("minecraft:cave_air", BlockType::Air),
+            ("minecraft:void_air", BlockType::Air),
+            ("minecraft:grass_block", BlockType::Grass),
+            ("minecraft:water", BlockType::Water),
+            ("minecraft:dirt", BlockType::Dirt),
+            ("minecraft:coarse_dirt", BlockType::Dirt),
+            ("minecraft:stone", BlockType::Stone),
+            ("minecraft:cobblestone", BlockType::Stone),
+            ("minecraft:sand", BlockType::Sand),
+            ("minecraft:oak_leaves", BlockType::Leaf),
+            ("minecraft:birch_leaves", BlockType::Leaf),
+            ("minecraft:spruce_leaves", BlockType::Leaf),
+            ("minecraft:jungle_leaves", BlockType::Leaf),
+            ("minecraft:coal_ore", BlockType::CoalOre),
+            ("minecraft:iron_ore", BlockType::IronOre),
+            ("minecraft:gold_ore", BlockType::GoldOre),
+            ("minecraft:diamond_ore", BlockType::DiamondOre),
+            ("minecraft:oak_log", BlockType::Log),
+            ("minecraft:spruce_log", BlockType::Log),
+            ("minecraft:jungle_log", BlockType::Log),
+            ("minecraft:oak_planks", BlockType::Log),
+            ("minecraft:birch_log", BlockType::BirchLog),
+        ]
+        .iter()
+        .map(|(name, block)| (name.to_string(), TemplateBlock::Place(*block)))
+        .chain(std::iter::once((
+            "minecraft:structure_void".to_string(),
+            TemplateBlock::Keep,
+        )))
+        .collect();
+        let export = [
+            (BlockType::Grass, "minecraft:grass_block"),
+            (BlockType::Water, "minecraft:water"),
+            (BlockType::Dirt, "minecraft:dirt"),
+            (BlockType::Stone, "minecraft:stone"),
+            (BlockType::Sand, "minecraft:sand"),
+            (BlockType::Air, "minecraft:air"),
+            (BlockType::Leaf, "minecraft:oak_leaves"),
+            (BlockType::CoalOre, "minecraft:coal_ore"),
+            (BlockType::IronOre, "minecraft:iron_ore"),
+            (BlockType::GoldOre, "minecraft:gold_ore"),
+            (BlockType::DiamondOre, "minecraft:diamond_ore"),
+            (BlockType::Log, "minecraft:oak_log"),
+            (BlockType::BirchLog, "minecraft:birch_log"),
+            (BlockType::Unknown, "minecraft:structure_void"),
+        ]
+        .iter()
+        .map(|(block, name)| (*block, name.to_string()))
+        .collect();
+        BlockNameMapping {
+            import,
+            unknown: TemplateBlock::Place(BlockType::Stone),
+            export,
+        }
+    }
+}
+
+// the schematic offset becomes the template origin, so pasting lines up the same way as in minecraft tools
+pub fn import_schematic(filename: &str, mapping: &BlockNameMapping) -> Result<StructureTemplate, VoxIoError> {
+    let schematic = read_schematic_from_file(filename)?;
+    let palette: Vec<TemplateBlock> = schematic.palette.iter().map(|name| mapping.to_block(name)).collect();
+    for name in schematic.palette.iter() {
+        let base = name.split('[').next().unwrap_or(name);
+        if !mapping.import.contains_key(name) && !mapping.import.contains_key(base) {
+            println!("schematic {} uses unmapped block {}", filename, name);
+        }
+    }
+    return Ok(StructureTemplate::from_grid(
+        filename,
+        [schematic.width as u32, schematic.height as u32, schematic.length as u32],
+        [-schematic.offset[0], -schematic.offset[1], -schematic.offset[2]],
+        |x, y, z| palette[schematic.blocks[schematic.index(x, y, z)] as usize],
+    ));
+}
+
+// pastes the schematic with its origin at pos, the metachunks it covers are loaded or generated first
+pub fn paste_schematic(
+    world: &mut SmallWorld,
+    generator: &ChunkGenerator,
+    filename: &str,
+    pos: &GlobalBlockPos,
+    mapping: &BlockNameMapping,
+) -> Result<(), VoxIoError> {
+    let template = import_schematic(filename, mapping)?;
+    let size = template.size();
+    let min = pos.get_diff(-template.origin[0], -template.origin[1], -template.origin[2]);
+    let max = min.get_diff(size[0] as i32 - 1, size[1] as i32 - 1, size[2] as i32 - 1);
+    world.load_area(generator, &min, &max);
+    template.place(pos, &Placement::default(), world);
+    return Ok(());
+}
+
+// exports the box between min and max (inclusive), unloaded blocks become structure voids
+pub fn export_schematic(
+    world: &SmallWorld,
+    min: &GlobalBlockPos,
+    max: &GlobalBlockPos,
+    filename: &str,
+    mapping: &BlockNameMapping,
+    version: i32,
+) -> Result<(), VoxIoError> {
+    let low = [min.x.min(max.x), min.y.min(max.y), min.z.min(max.z)];
+    let high = [min.x.max(max.x), min.y.max(max.y), min.z.max(max.z)];
+    // the schematic stores its size as shorts
+    let mut size = [0u16; 3];
+    for i in 0..3 {
+        let extent = high[i] as i64 - low[i] as i64 + 1;
+        if extent > u16::MAX as i64 {
+            return Err(VoxIoError::Io(
+                filename.to_string(),
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("the region is {} blocks long, schematics hold at most {}", extent, u16::MAX),
+                ),
+            ));
+        }
+        size[i] = extent as u16;
+    }
+    let mut palette: Vec<String> = Vec::new();
+    let mut palette_indices: HashMap<String, u32> = HashMap::new();
+    let mut blocks = Vec::new();
+    for y in low[1]..=high[1] {
+        for z in low[2]..=high[2] {
+            for x in low[0]..=high[0] {
+                let name = mapping.to_name(get_blocktype(world.get_block(GlobalBlockPos { x, y, z })));
+                let index = match palette_indices.get(&name) {
+                    Some(i) => *i,
+                    None => {
+                        let i = palette.len() as u32;
+                        palette_indices.insert(name.clone(), i);
+                        palette.push(name);
+                        i
+                    }
+                };
+                blocks.push(index);
+            }
+        }
+    }
+    return write_schematic_to_file(
+        filename,
+        &SpongeSchematic {
+            version,
+            data_version: DATA_VERSION,
+            width: size[0],
+            height: size[1],
+            length: size[2],
+            offset: [0, 0, 0],
+            palette,
+            blocks,
+        },
+    );
+}
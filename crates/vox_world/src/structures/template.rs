@@ -0,0 +1,212 @@
+use crate::blocks::block::{get_blockid, BlockId};
+use crate::blocks::block_type::BlockType;
+use crate::world::small_world::SmallWorld;
+use crate::world_gen::meta_chunk::MetaChunk;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use vox_core::constants::{CHUNKSIZE, METACHUNKSIZE};
+use vox_core::positions::GlobalBlockPos;
+use vox_io::io::config_reader::read_config_from_file;
+use vox_io::io::error::VoxIoError;
+
+pub const STRUCTURE_DIR: &str = "structures";
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum TemplateBlock {
+    // also used with Air to carve out space
+    Place(BlockType),
+    // leaves whatever is in the world at this position
+    Keep,
+}
+
+// rotation around the y axis, clockwise when looking down
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Rotation {
+    None,
+    Clockwise90,
+    Clockwise180,
+    Clockwise270,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Placement {
+    pub rotation: Rotation,
+    // mirroring is applied before the rotation
+    pub mirror_x: bool,
+    pub mirror_z: bool,
+}
+
+// layers go from the bottom up, every layer is a list of rows along z and every character in a row
+// is one block along x, looked up in the palette
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StructureTemplate {
+    pub name: String,
+    pub palette: HashMap<char, TemplateBlock>,
+    // template position that ends up at the position the template is placed at
+    pub origin: [i32; 3],
+    pub layers: Vec<Vec<String>>,
+}
+
+pub trait PlacementTarget {
+    fn contains_block(&self, pos: &GlobalBlockPos) -> bool;
+    fn place_block(&mut self, pos: &GlobalBlockPos, block: BlockId);
+}
+
+impl Rotation {
+    pub fn from_index(index: u32) -> Rotation {
+        match index % 4 {
+            0 => Rotation::None,
+            1 => Rotation::Clockwise90,
+            2 => Rotation::Clockwise180,
+            _ => Rotation::Clockwise270,
+        }
+    }
+}
+
+impl Default for Placement {
+    fn default() -> Self {
+        Placement {
+            rotation: Rotation::None,
+            mirror_x: false,
+            mirror_z: false,
+        }
+    }
+}
+
+impl Placement {
+    // offset of a template block relative to the origin, to an offset in the world
+    pub fn transform(&self, x: i32, z: i32) -> (i32, i32) {
+        let x = if self.mirror_x { -x } else { x };
+        let z = if self.mirror_z { -z } else { z };
+        match self.rotation {
+            Rotation::None => (x, z),
+            Rotation::Clockwise90 => (-z, x),
+            Rotation::Clockwise180 => (-x, -z),
+            Rotation::Clockwise270 => (z, -x),
+        }
+    }
+}
+
+impl StructureTemplate {
+    pub fn load(filename: &str) -> Result<StructureTemplate, VoxIoError> {
+        let template: StructureTemplate = read_config_from_file(filename)?;
+        return match template.validate() {
+            Ok(_) => Ok(template),
+            Err(e) => Err(VoxIoError::Corrupt(filename.to_string(), e)),
+        };
+    }
+    // builds a template from any voxel grid, used by the importers
+    pub fn from_grid(
+        name: &str,
+        size: [u32; 3],
+        origin: [i32; 3],
+        get_block: impl Fn(u32, u32, u32) -> TemplateBlock,
+    ) -> StructureTemplate {
+        let mut palette = HashMap::new();
+        palette.insert('.', TemplateBlock::Keep);
+        let mut layers = Vec::with_capacity(size[1] as usize);
+        for y in 0..size[1] {
+            let mut layer = Vec::with_capacity(size[2] as usize);
+            for z in 0..size[2] {
+                let row: String = (0..size[0])
+                    .map(|x| match get_block(x, y, z) {
+                        TemplateBlock::Keep => '.',
+                        TemplateBlock::Place(block) => {
+                            let c = palette_char(block);
+                            palette.insert(c, TemplateBlock::Place(block));
+                            c
+                        }
+                    })
+                    .collect();
+                layer.push(row);
+            }
+            layers.push(layer);
+        }
+        return StructureTemplate {
+            name: name.to_string(),
+            palette,
+            origin,
+            layers,
+        };
+    }
+    pub fn load_by_name(name: &str) -> Result<StructureTemplate, VoxIoError> {
+        StructureTemplate::load(&format!("{}/{}.ron", STRUCTURE_DIR, name))
+    }
+    pub fn validate(&self) -> Result<(), String> {
+        let size = self.size();
+        for (y, layer) in self.layers.iter().enumerate() {
+            if layer.len() != size[2] as usize {
+                return Err(format!("layer {} has {} rows instead of {}", y, layer.len(), size[2]));
+            }
+            for (z, row) in layer.iter().enumerate() {
+                if row.chars().count() != size[0] as usize {
+                    return Err(format!("row {} of layer {} is not {} blocks long", z, y, size[0]));
+                }
+                if let Some(c) = row.chars().find(|c| !self.palette.contains_key(c)) {
+                    return Err(format!("'{}' in layer {} is not in the palette", c, y));
+                }
+            }
+        }
+        return Ok(());
+    }
+    // size in blocks along x, y and z
+    pub fn size(&self) -> [u32; 3] {
+        let depth = self.layers.first().map_or(0, |layer| layer.len());
+        let width = self
+            .layers
+            .first()
+            .and_then(|layer| layer.first())
+            .map_or(0, |row| row.chars().count());
+        [width as u32, self.layers.len() as u32, depth as u32]
+    }
+    pub fn get(&self, x: u32, y: u32, z: u32) -> TemplateBlock {
+        let c = self.layers[y as usize][z as usize].chars().nth(x as usize).unwrap();
+        return self.palette[&c];
+    }
+    // blocks that would land outside of the target are skipped
+    pub fn place(&self, pos: &GlobalBlockPos, placement: &Placement, target: &mut impl PlacementTarget) {
+        let size = self.size();
+        for y in 0..size[1] {
+            for z in 0..size[2] {
+                for x in 0..size[0] {
+                    let block = match self.get(x, y, z) {
+                        TemplateBlock::Keep => continue,
+                        TemplateBlock::Place(block) => block,
+                    };
+                    let (dx, dz) = placement.transform(x as i32 - self.origin[0], z as i32 - self.origin[2]);
+                    let world_pos = pos.get_diff(dx, y as i32 - self.origin[1], dz);
+                    if target.contains_block(&world_pos) {
+                        target.place_block(&world_pos, get_blockid(block));
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn palette_char(block: BlockType) -> char {
+    match block {
+        BlockType::Air => ' ',
+        _ => std::char::from_u32('a' as u32 + get_blockid(block) as u32).unwrap_or('?'),
+    }
+}
+
+impl PlacementTarget for MetaChunk {
+    fn contains_block(&self, pos: &GlobalBlockPos) -> bool {
+        MetaChunk::contains_block(self, pos)
+    }
+    fn place_block(&mut self, pos: &GlobalBlockPos, block: BlockId) {
+        self.set_block(pos, block)
+    }
+}
+
+impl PlacementTarget for SmallWorld {
+    fn contains_block(&self, pos: &GlobalBlockPos) -> bool {
+        pos.y >= 0
+            && pos.y < (CHUNKSIZE * METACHUNKSIZE) as i32
+            && self.get_meta_chunk(&pos.get_meta_chunk_pos()).is_some()
+    }
+    fn place_block(&mut self, pos: &GlobalBlockPos, block: BlockId) {
+        self.set_block(block, *pos)
+    }
+}
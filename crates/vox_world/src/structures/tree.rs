@@ -1,27 +1,245 @@
-use crate::blocks::block::{get_blockid, get_blocktype};
+use crate::blocks::block::{get_blockid, get_blocktype, BlockId};
 use crate::blocks::block_type::BlockType;
 use crate::world_gen::meta_chunk::MetaChunk;
-use rand::distributions::{Distribution, Uniform};
+use rand::rngs::StdRng;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
 use vox_core::positions::GlobalBlockPos;
 
-pub fn place_tree(pos: &GlobalBlockPos, world: &mut MetaChunk) {
-    let mut rng = rand::thread_rng();
-    let height_range = Uniform::from(8..12);
-    let height = height_range.sample(&mut rng);
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum CanopyShape {
+    None,
+    Sphere,
+    // narrows towards the top, radius is the width at the bottom
+    Cone,
+    // flat wide disc of leaves around the top of the trunk
+    Umbrella,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TreeSpecies {
+    pub name: String,
+    pub trunk: BlockType,
+    pub leaves: BlockType,
+    // the block the tree has to be planted on
+    pub ground: BlockType,
+    pub min_height: i32,
+    pub max_height: i32,
+    // 1 for a single column, 2 for a 2x2 trunk
+    pub trunk_width: i32,
+    pub branches: u32,
+    pub branch_length: i32,
+    pub canopy: CanopyShape,
+    pub canopy_radius: i32,
+    pub roots: u32,
+    pub root_length: i32,
+}
+
+impl TreeSpecies {
+    pub fn oak() -> TreeSpecies {
+        TreeSpecies {
+            name: "oak".to_string(),
+            trunk: BlockType::Log,
+            leaves: BlockType::Leaf,
+            ground: BlockType::Grass,
+            min_height: 5,
+            max_height: 8,
+            trunk_width: 1,
+            branches: 3,
+            branch_length: 3,
+            canopy: CanopyShape::Sphere,
+            canopy_radius: 3,
+            roots: 0,
+            root_length: 0,
+        }
+    }
+    pub fn pine() -> TreeSpecies {
+        TreeSpecies {
+            name: "pine".to_string(),
+            trunk: BlockType::Log,
+            leaves: BlockType::Leaf,
+            ground: BlockType::Grass,
+            min_height: 9,
+            max_height: 14,
+            trunk_width: 1,
+            branches: 0,
+            branch_length: 0,
+            canopy: CanopyShape::Cone,
+            canopy_radius: 4,
+            roots: 0,
+            root_length: 0,
+        }
+    }
+    pub fn birch() -> TreeSpecies {
+        TreeSpecies {
+            name: "birch".to_string(),
+            trunk: BlockType::BirchLog,
+            leaves: BlockType::Leaf,
+            ground: BlockType::Grass,
+            min_height: 6,
+            max_height: 9,
+            trunk_width: 1,
+            branches: 0,
+            branch_length: 0,
+            canopy: CanopyShape::Sphere,
+            canopy_radius: 2,
+            roots: 0,
+            root_length: 0,
+        }
+    }
+    pub fn jungle() -> TreeSpecies {
+        TreeSpecies {
+            name: "jungle".to_string(),
+            trunk: BlockType::Log,
+            leaves: BlockType::Leaf,
+            ground: BlockType::Grass,
+            min_height: 14,
+            max_height: 22,
+            trunk_width: 2,
+            branches: 4,
+            branch_length: 4,
+            canopy: CanopyShape::Umbrella,
+            canopy_radius: 5,
+            roots: 4,
+            root_length: 3,
+        }
+    }
+    pub fn dead_bush() -> TreeSpecies {
+        TreeSpecies {
+            name: "dead bush".to_string(),
+            trunk: BlockType::Log,
+            leaves: BlockType::Air,
+            ground: BlockType::Sand,
+            min_height: 1,
+            max_height: 3,
+            trunk_width: 1,
+            branches: 2,
+            branch_length: 1,
+            canopy: CanopyShape::None,
+            canopy_radius: 0,
+            roots: 0,
+            root_length: 0,
+        }
+    }
+    pub fn from_name(name: &str) -> Option<TreeSpecies> {
+        match name {
+            "oak" => Some(TreeSpecies::oak()),
+            "pine" => Some(TreeSpecies::pine()),
+            "birch" => Some(TreeSpecies::birch()),
+            "jungle" => Some(TreeSpecies::jungle()),
+            "dead bush" => Some(TreeSpecies::dead_bush()),
+            _ => None,
+        }
+    }
+    // place_tree needs a trunk and a height range to pick from, the other lengths may be 0
+    pub fn validate(&self) -> Result<(), String> {
+        if self.min_height <= 0 || self.max_height < self.min_height {
+            return Err(format!(
+                "{} needs 0 < min_height <= max_height, got {} and {}",
+                self.name, self.min_height, self.max_height
+            ));
+        }
+        if self.trunk_width <= 0 {
+            return Err(format!("{} needs a positive trunk_width, got {}", self.name, self.trunk_width));
+        }
+        if self.branch_length < 0 || self.canopy_radius < 0 || self.root_length < 0 {
+            return Err(format!("{} has a negative branch, canopy or root size", self.name));
+        }
+        return Ok(());
+    }
+}
+
+// pos is the lowest trunk block, the rng decides the shape so the same rng state gives the same tree
+pub fn place_tree(pos: &GlobalBlockPos, world: &mut MetaChunk, species: &TreeSpecies, rng: &mut StdRng) {
+    let height = rng.gen_range(species.min_height..=species.max_height.max(species.min_height));
+    let trunk = get_blockid(species.trunk);
+
     for y in 0..height {
-        if y < height - 2 {
-            world.set_block(&pos.get_diff(0, y, 0), get_blockid(BlockType::Sand));
-        }
-        if y >= 4 {
-            for x in -(height - y - 1)..height - y {
-                for z in -(height - y - 1)..height - y {
-                    let currect_block = world.get_block(&pos.get_diff(x, y, z));
-                    if get_blocktype(currect_block) == BlockType::Air
-                    {
-                        world.set_block(&pos.get_diff(x, y, z), get_blockid(BlockType::Leaf));
-                    }
+        for x in 0..species.trunk_width {
+            for z in 0..species.trunk_width {
+                set_if_replaceable(world, &pos.get_diff(x, y, z), trunk);
+            }
+        }
+    }
+
+    for _ in 0..species.roots {
+        let dir_x = rng.gen_range(-1..=1);
+        let dir_z = if dir_x == 0 { if rng.gen() { 1 } else { -1 } } else { rng.gen_range(-1..=1) };
+        let mut root = pos.get_diff(dir_x.max(0) * (species.trunk_width - 1), 0, dir_z.max(0) * (species.trunk_width - 1));
+        for i in 0..species.root_length {
+            root = root.get_diff(dir_x, if i % 2 == 1 { -1 } else { 0 }, dir_z);
+            set_if_replaceable(world, &root, trunk);
+        }
+    }
+
+    for _ in 0..species.branches {
+        let start_y = rng.gen_range(height / 2..height.max(height / 2 + 1));
+        let dir_x = rng.gen_range(-1..=1);
+        let dir_z = if dir_x == 0 { if rng.gen() { 1 } else { -1 } } else { rng.gen_range(-1..=1) };
+        let mut branch = pos.get_diff(0, start_y, 0);
+        for i in 0..species.branch_length {
+            branch = branch.get_diff(dir_x, if i % 2 == 0 { 1 } else { 0 }, dir_z);
+            set_if_replaceable(world, &branch, trunk);
+        }
+        if species.canopy != CanopyShape::None {
+            place_leaves_sphere(world, &branch, (species.canopy_radius / 2).max(1), species.leaves);
+        }
+    }
+
+    let centre = pos.get_diff(species.trunk_width / 2, height - 1, species.trunk_width / 2);
+    match species.canopy {
+        CanopyShape::None => {}
+        CanopyShape::Sphere => place_leaves_sphere(world, &centre, species.canopy_radius, species.leaves),
+        CanopyShape::Cone => {
+            let bottom = height / 3;
+            for y in bottom..=height {
+                let radius = species.canopy_radius * (height - y) / (height - bottom).max(1);
+                place_leaves_disc(world, &pos.get_diff(0, y, 0), radius, species.leaves);
+            }
+        }
+        CanopyShape::Umbrella => {
+            place_leaves_disc(world, &centre, species.canopy_radius, species.leaves);
+            place_leaves_disc(world, &centre.get_diff(0, 1, 0), species.canopy_radius - 1, species.leaves);
+        }
+    }
+}
+
+fn place_leaves_sphere(world: &mut MetaChunk, centre: &GlobalBlockPos, radius: i32, leaves: BlockType) {
+    for x in -radius..=radius {
+        for y in -radius..=radius {
+            for z in -radius..=radius {
+                if x * x + y * y + z * z <= radius * radius + 1 {
+                    set_if_air(world, &centre.get_diff(x, y, z), get_blockid(leaves));
                 }
             }
         }
     }
 }
+
+fn place_leaves_disc(world: &mut MetaChunk, centre: &GlobalBlockPos, radius: i32, leaves: BlockType) {
+    for x in -radius..=radius {
+        for z in -radius..=radius {
+            if x * x + z * z <= radius * radius + 1 {
+                set_if_air(world, &centre.get_diff(x, 0, z), get_blockid(leaves));
+            }
+        }
+    }
+}
+
+// wood grows through air, leaves and soft ground but never replaces other blocks,
+// parts of the tree outside of the metachunk are cut off
+fn set_if_replaceable(world: &mut MetaChunk, pos: &GlobalBlockPos, block: BlockId) {
+    if !world.contains_block(pos) {
+        return;
+    }
+    match get_blocktype(world.get_block(pos)) {
+        BlockType::Air | BlockType::Leaf | BlockType::Dirt | BlockType::Grass => world.set_block(pos, block),
+        _ => {}
+    }
+}
+
+fn set_if_air(world: &mut MetaChunk, pos: &GlobalBlockPos, block: BlockId) {
+    if world.contains_block(pos) && get_blocktype(world.get_block(pos)) == BlockType::Air {
+        world.set_block(pos, block);
+    }
+}
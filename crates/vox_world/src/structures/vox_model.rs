@@ -0,0 +1,191 @@
+use crate::blocks::block::{get_blocktype, placeable_blocks};
+use crate::blocks::block_type::BlockType;
+use crate::structures::template::{StructureTemplate, TemplateBlock};
+use crate::world::small_world::SmallWorld;
+use std::collections::HashMap;
+use vox_core::constants::COLORS;
+use vox_core::positions::GlobalBlockPos;
+use vox_io::io::error::VoxIoError;
+use vox_io::io::magica_voxel::{read_vox_from_file, write_vox_to_file, VoxModel, VoxScene};
+
+// MagicaVoxel models can't be bigger than this along any axis
+const MAX_MODEL_SIZE: i32 = 256;
+// a merged scene is a dense template, so its bounding box may hold as many blocks as 4 full models
+const MAX_SCENE_VOLUME: i64 = 4 * 256 * 256 * 256;
+
+// palette index block id + 1 holds the color of that block
+pub fn block_palette() -> [[u8; 4]; 256] {
+    let mut palette = [[128, 128, 128, 255]; 256];
+    palette[0] = [0, 0, 0, 0];
+    for id in placeable_blocks() {
+        let color = COLORS[id as usize];
+        palette[id as usize + 1] = [color[0] as u8, color[1] as u8, color[2] as u8, 255];
+    }
+    return palette;
+}
+
+pub fn block_for_color(color: [u8; 4]) -> BlockType {
+    let mut best = BlockType::Stone;
+    let mut best_distance = f32::MAX;
+    for id in placeable_blocks() {
+        let block_color = COLORS[id as usize];
+        let distance: f32 = (0..3).map(|c| (block_color[c] - color[c] as f32).powi(2)).sum();
+        if distance < best_distance {
+            best = get_blocktype(id);
+            best_distance = distance;
+        }
+    }
+    return best;
+}
+
+// every model becomes its own template, empty voxels keep what is already in the world
+pub fn import_vox_models(filename: &str) -> Result<Vec<StructureTemplate>, VoxIoError> {
+    let scene = read_vox_from_file(filename)?;
+    let mapping = palette_mapping(&scene);
+    return Ok(
+        scene
+            .models
+            .iter()
+            .enumerate()
+            .map(|(i, model)| {
+                // vox files are z up, so their y and z are swapped with ours
+                let mut blocks = HashMap::new();
+                for v in &model.voxels {
+                    blocks.insert((v[0] as u32, v[2] as u32, v[1] as u32), mapping[v[3] as usize]);
+                }
+                let size = [model.size[0], model.size[2], model.size[1]];
+                StructureTemplate::from_grid(
+                    &format!("{} {}", filename, i),
+                    size,
+                    [size[0] as i32 / 2, 0, size[2] as i32 / 2],
+                    |x, y, z| grid_block(&blocks, x, y, z),
+                )
+            })
+            .collect(),
+    );
+}
+
+// merges all models of the scene into a single template using their scene positions, scenes
+// whose models are spread too far apart have to be imported with import_vox_models
+pub fn import_vox_scene(filename: &str) -> Result<StructureTemplate, VoxIoError> {
+    let scene = read_vox_from_file(filename)?;
+    let mapping = palette_mapping(&scene);
+    let mut blocks = HashMap::new();
+    for model in &scene.models {
+        for v in &model.voxels {
+            let x = model.position[0] + v[0] as i32;
+            let y = model.position[2] + v[2] as i32;
+            let z = model.position[1] + v[1] as i32;
+            blocks.insert((x, y, z), mapping[v[3] as usize]);
+        }
+    }
+    if blocks.is_empty() {
+        return Ok(StructureTemplate::from_grid(filename, [0, 0, 0], [0, 0, 0], |_, _, _| TemplateBlock::Keep));
+    }
+    let min = [
+        blocks.keys().map(|p| p.0).min().unwrap(),
+        blocks.keys().map(|p| p.1).min().unwrap(),
+        blocks.keys().map(|p| p.2).min().unwrap(),
+    ];
+    let max = [
+        blocks.keys().map(|p| p.0).max().unwrap(),
+        blocks.keys().map(|p| p.1).max().unwrap(),
+        blocks.keys().map(|p| p.2).max().unwrap(),
+    ];
+    let extent: Vec<i64> = (0..3).map(|i| max[i] as i64 - min[i] as i64 + 1).collect();
+    if extent.iter().product::<i64>() > MAX_SCENE_VOLUME {
+        return Err(VoxIoError::Io(
+            filename.to_string(),
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "the models span {}x{}x{} blocks, import them one by one instead",
+                    extent[0], extent[1], extent[2]
+                ),
+            ),
+        ));
+    }
+    let size = [extent[0] as u32, extent[1] as u32, extent[2] as u32];
+    let blocks: HashMap<(u32, u32, u32), BlockType> = blocks
+        .into_iter()
+        .map(|((x, y, z), b)| (((x - min[0]) as u32, (y - min[1]) as u32, (z - min[2]) as u32), b))
+        .collect();
+    return Ok(StructureTemplate::from_grid(
+        filename,
+        size,
+        [size[0] as i32 / 2, 0, size[2] as i32 / 2],
+        |x, y, z| grid_block(&blocks, x, y, z),
+    ));
+}
+
+// writes every loaded block between min and max (inclusive) except air, regions wider than
+// a single vox model are split into several models
+pub fn export_vox_region(
+    world: &SmallWorld,
+    min: &GlobalBlockPos,
+    max: &GlobalBlockPos,
+    filename: &str,
+) -> Result<(), VoxIoError> {
+    let low = [min.x.min(max.x), min.y.min(max.y), min.z.min(max.z)];
+    let high = [min.x.max(max.x), min.y.max(max.y), min.z.max(max.z)];
+    let mut models = Vec::new();
+    let mut start_x = low[0];
+    while start_x <= high[0] {
+        let end_x = (start_x + MAX_MODEL_SIZE - 1).min(high[0]);
+        let mut start_z = low[2];
+        while start_z <= high[2] {
+            let end_z = (start_z + MAX_MODEL_SIZE - 1).min(high[2]);
+            let mut start_y = low[1];
+            while start_y <= high[1] {
+                let end_y = (start_y + MAX_MODEL_SIZE - 1).min(high[1]);
+                let mut voxels = Vec::new();
+                for x in start_x..=end_x {
+                    for y in start_y..=end_y {
+                        for z in start_z..=end_z {
+                            let block = world.get_block(GlobalBlockPos { x, y, z });
+                            match get_blocktype(block) {
+                                BlockType::Air | BlockType::Unknown => {}
+                                _ => voxels.push([
+                                    (x - start_x) as u8,
+                                    (z - start_z) as u8,
+                                    (y - start_y) as u8,
+                                    block + 1,
+                                ]),
+                            }
+                        }
+                    }
+                }
+                models.push(VoxModel {
+                    size: [
+                        (end_x - start_x + 1) as u32,
+                        (end_z - start_z + 1) as u32,
+                        (end_y - start_y + 1) as u32,
+                    ],
+                    voxels,
+                    position: [start_x - low[0], start_z - low[2], start_y - low[1]],
+                });
+                start_y = end_y + 1;
+            }
+            start_z = end_z + 1;
+        }
+        start_x = end_x + 1;
+    }
+    return write_vox_to_file(
+        filename,
+        &VoxScene {
+            models,
+            palette: block_palette(),
+        },
+    );
+}
+
+fn palette_mapping(scene: &VoxScene) -> Vec<BlockType> {
+    scene.palette.iter().map(|color| block_for_color(*color)).collect()
+}
+
+fn grid_block(blocks: &HashMap<(u32, u32, u32), BlockType>, x: u32, y: u32, z: u32) -> TemplateBlock {
+    match blocks.get(&(x, y, z)) {
+        Some(block) => TemplateBlock::Place(*block),
+        None => TemplateBlock::Keep,
+    }
+}
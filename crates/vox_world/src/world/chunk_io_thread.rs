@@ -0,0 +1,265 @@
+use crate::world::small_world::SmallWorld;
+use crate::world_gen::meta_chunk::MetaChunk;
+use std::collections::{HashMap, VecDeque};
+use std::sync::mpsc;
+use std::sync::mpsc::{Receiver, Sender, TryRecvError};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::thread::JoinHandle;
+use std::time::Instant;
+use vox_core::positions::MetaChunkPos;
+use vox_io::io::compression::CompressionStats;
+use vox_io::io::error::VoxIoError;
+
+pub enum ChunkIoResult {
+    Loaded(MetaChunk, MetaChunkPos),
+    // there is no usable save, the metachunk has to be generated with the seed
+    Missing(MetaChunkPos, u32),
+    Saved(MetaChunkPos, CompressionStats),
+    // the serialized metachunk is kept and written again by retry_failed_saves
+    SaveFailed(MetaChunkPos, VoxIoError),
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ChunkIoMetrics {
+    pub pending_loads: usize,
+    pub pending_saves: usize,
+    pub loaded: u64,
+    // only saves that were written, failed writes are counted in failed
+    pub saved: u64,
+    pub failed: u64,
+    // saves that were replaced by a newer save of the same metachunk before being written
+    pub coalesced: u64,
+    pub average_load_time: f32,
+    pub average_save_time: f32,
+}
+
+struct IoState {
+    loads: VecDeque<(MetaChunkPos, u32)>,
+    // serialized metachunks waiting to be written, a newer save replaces the pending one
+    saves: HashMap<MetaChunkPos, Vec<u8>>,
+    // saves taken off the map that are still being written
+    saving: usize,
+    // saves that could not be written, kept until they are retried or replaced by a newer save
+    failed: HashMap<MetaChunkPos, Vec<u8>>,
+    shutdown: bool,
+    metrics: ChunkIoMetrics,
+    total_load_time: f32,
+    total_save_time: f32,
+}
+
+pub struct ChunkIoThread {
+    state: Arc<(Mutex<IoState>, Condvar)>,
+    // signalled whenever the thread runs out of saves, flush waits on it
+    idle: Arc<Condvar>,
+    pub chunk_io_receiver: Receiver<ChunkIoResult>,
+    pub thread: Option<JoinHandle<()>>,
+}
+
+impl ChunkIoThread {
+    pub fn new(save_dir: &str) -> ChunkIoThread {
+        let state = Arc::new((
+            Mutex::new(IoState {
+                loads: VecDeque::new(),
+                saves: HashMap::new(),
+                saving: 0,
+                failed: HashMap::new(),
+                shutdown: false,
+                metrics: ChunkIoMetrics::default(),
+                total_load_time: 0.0,
+                total_save_time: 0.0,
+            }),
+            Condvar::new(),
+        ));
+        let idle = Arc::new(Condvar::new());
+        let (sender, receiver) = mpsc::channel();
+        let thread = {
+            let state = state.clone();
+            let idle = idle.clone();
+            let save_dir = save_dir.to_string();
+            thread::Builder::new()
+                .name("chunk io".to_string())
+                .spawn(move || io_loop(state, idle, sender, save_dir))
+                .unwrap()
+        };
+        return ChunkIoThread {
+            state,
+            idle,
+            chunk_io_receiver: receiver,
+            thread: Some(thread),
+        };
+    }
+    pub fn load(&self, pos: MetaChunkPos, seed: u32) {
+        let (lock, condvar) = &*self.state;
+        let mut state = lock.lock().unwrap();
+        state.loads.push_back((pos, seed));
+        state.metrics.pending_loads = state.loads.len();
+        condvar.notify_one();
+    }
+    // raw is the output of MetaChunk::serialize
+    pub fn save(&self, pos: MetaChunkPos, raw: Vec<u8>) {
+        let (lock, condvar) = &*self.state;
+        let mut state = lock.lock().unwrap();
+        state.failed.remove(&pos);
+        if state.saves.insert(pos, raw).is_some() {
+            state.metrics.coalesced += 1;
+        }
+        state.metrics.pending_saves = state.saves.len();
+        condvar.notify_one();
+    }
+    // queues the failed saves again, a newer save of the same metachunk takes precedence
+    pub fn retry_failed_saves(&self) {
+        let (lock, condvar) = &*self.state;
+        let mut state = lock.lock().unwrap();
+        let failed: Vec<(MetaChunkPos, Vec<u8>)> = state.failed.drain().collect();
+        for (pos, raw) in failed {
+            state.saves.entry(pos).or_insert(raw);
+        }
+        state.metrics.pending_saves = state.saves.len();
+        condvar.notify_one();
+    }
+    // queues every modified metachunk of the world together with the failed saves, dropping the
+    // thread afterwards still writes them
+    pub fn save_modified_chunks(&self, world: &mut SmallWorld) {
+        self.retry_failed_saves();
+        for (pos, raw) in world.take_modified_chunks() {
+            self.save(pos, raw);
+        }
+    }
+    pub fn failed_saves(&self) -> usize {
+        let (lock, _) = &*self.state;
+        lock.lock().unwrap().failed.len()
+    }
+    // blocks until every save requested before this call is written to disk, or failed to be
+    pub fn flush(&self) {
+        let (lock, condvar) = &*self.state;
+        let mut state = lock.lock().unwrap();
+        condvar.notify_one();
+        while !state.saves.is_empty() || state.saving > 0 {
+            state = self.idle.wait(state).unwrap();
+        }
+    }
+    pub fn metrics(&self) -> ChunkIoMetrics {
+        let (lock, _) = &*self.state;
+        lock.lock().unwrap().metrics
+    }
+    pub fn get(&self) -> Result<ChunkIoResult, TryRecvError> {
+        self.chunk_io_receiver.try_recv()
+    }
+}
+
+impl Drop for ChunkIoThread {
+    // pending saves are still written before the thread stops
+    fn drop(&mut self) {
+        {
+            let (lock, condvar) = &*self.state;
+            lock.lock().unwrap().shutdown = true;
+            condvar.notify_all();
+        }
+        if let Some(thread) = self.thread.take() {
+            if thread.join().is_err() {
+                println!("chunk io thread panicked");
+            }
+        }
+    }
+}
+
+enum IoTask {
+    Load(MetaChunkPos, u32),
+    // a load of a metachunk whose last save failed, the file on disk is older than these bytes
+    Unsaved(MetaChunkPos, u32, Vec<u8>),
+    Save(MetaChunkPos, Vec<u8>),
+}
+
+fn io_loop(state: Arc<(Mutex<IoState>, Condvar)>, idle: Arc<Condvar>, sender: Sender<ChunkIoResult>, save_dir: String) {
+    let (lock, condvar) = &*state;
+    loop {
+        let task = {
+            let mut state = lock.lock().unwrap();
+            loop {
+                if state.shutdown {
+                    state.loads.clear();
+                }
+                // a load of a metachunk with a pending save has to wait for that save, otherwise
+                // it would read the old file
+                let pending_save = state.loads.front().map_or(false, |(pos, _)| state.saves.contains_key(pos));
+                if !state.loads.is_empty() && !pending_save {
+                    let (pos, seed) = state.loads.pop_front().unwrap();
+                    state.metrics.pending_loads = state.loads.len();
+                    if let Some(raw) = state.failed.remove(&pos) {
+                        break Some(IoTask::Unsaved(pos, seed, raw));
+                    }
+                    break Some(IoTask::Load(pos, seed));
+                }
+                let next_save = match state.loads.front() {
+                    Some((pos, _)) if pending_save => Some(*pos),
+                    _ => state.saves.keys().next().copied(),
+                };
+                if let Some(pos) = next_save {
+                    let raw = state.saves.remove(&pos).unwrap();
+                    state.saving += 1;
+                    state.metrics.pending_saves = state.saves.len();
+                    break Some(IoTask::Save(pos, raw));
+                }
+                idle.notify_all();
+                if state.shutdown {
+                    break None;
+                }
+                state = condvar.wait(state).unwrap();
+            }
+        };
+        let timer = Instant::now();
+        let result = match task {
+            None => return,
+            Some(IoTask::Load(pos, seed)) => {
+                let result = match MetaChunk::load_saved(&save_dir, pos, seed) {
+                    Some(chunk) => ChunkIoResult::Loaded(chunk, pos),
+                    None => ChunkIoResult::Missing(pos, seed),
+                };
+                let mut state = lock.lock().unwrap();
+                state.metrics.loaded += 1;
+                state.total_load_time += timer.elapsed().as_secs_f32();
+                state.metrics.average_load_time = state.total_load_time / state.metrics.loaded as f32;
+                result
+            }
+            Some(IoTask::Unsaved(pos, seed, raw)) => match MetaChunk::deserialize(&save_dir, &pos, &raw) {
+                // it still has to be saved, so it stays modified
+                Ok(mut chunk) => {
+                    chunk.modified = true;
+                    ChunkIoResult::Loaded(chunk, pos)
+                }
+                Err(e) => {
+                    println!("could not restore unsaved metachunk {:?}, {}, loading it from disk", pos, e);
+                    match MetaChunk::load_saved(&save_dir, pos, seed) {
+                        Some(chunk) => ChunkIoResult::Loaded(chunk, pos),
+                        None => ChunkIoResult::Missing(pos, seed),
+                    }
+                }
+            },
+            Some(IoTask::Save(pos, raw)) => {
+                let result = match MetaChunk::save_serialized(&save_dir, &pos, &raw) {
+                    Ok(stats) => ChunkIoResult::Saved(pos, stats),
+                    Err(e) => ChunkIoResult::SaveFailed(pos, e),
+                };
+                let mut state = lock.lock().unwrap();
+                state.saving -= 1;
+                match result {
+                    ChunkIoResult::SaveFailed(..) => {
+                        if !state.saves.contains_key(&pos) {
+                            state.failed.insert(pos, raw);
+                        }
+                        state.metrics.failed += 1;
+                    }
+                    _ => {
+                        state.metrics.saved += 1;
+                        state.total_save_time += timer.elapsed().as_secs_f32();
+                        state.metrics.average_save_time = state.total_save_time / state.metrics.saved as f32;
+                    }
+                }
+                result
+            }
+        };
+        // the receiver is gone while shutting down, the remaining saves are still written
+        let _ = sender.send(result);
+    }
+}
@@ -0,0 +1,90 @@
+use crate::player::Player;
+use crate::world::small_world::SmallWorld;
+use crate::world_gen::world_preset::WorldPreset;
+use serde::{Deserialize, Serialize};
+use vox_io::io::error::VoxIoError;
+use vox_io::io::file_reader::read_meta_chunk_from_file;
+use vox_io::io::file_writer::write_to_file;
+use vox_io::io::save_format::FORMAT_VERSION;
+
+pub const LEVEL_DATA_FILE: &str = "level.dat";
+// the previous level data is kept next to it, in case the new one turns out to be unreadable
+pub const LEVEL_DATA_BACKUP_FILE: &str = "level.dat_old";
+// seconds between automatic saves while playing
+pub const LEVEL_SAVE_INTERVAL: f32 = 60.0;
+
+// everything besides the chunks that is needed to resume a world
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LevelData {
+    // save format version the world was last written with
+    pub format_version: u32,
+    pub seed: u32,
+    pub preset: WorldPreset,
+    // seconds the world has been played
+    pub time: f64,
+    pub spawn: [f32; 3],
+    pub player_position: [f32; 3],
+    pub player_direction: [f32; 3],
+}
+
+impl LevelData {
+    pub fn new(seed: u32, preset: WorldPreset) -> LevelData {
+        let spawn = [0.5, 64.0, 0.5];
+        LevelData {
+            format_version: FORMAT_VERSION,
+            seed,
+            preset,
+            time: 0.0,
+            spawn,
+            player_position: spawn,
+            player_direction: [0.0, 0.0, 1.0],
+        }
+    }
+    // falls back to the backup when the level data itself can't be read
+    pub fn load(directory: &str) -> Result<LevelData, VoxIoError> {
+        let error = match LevelData::read(&format!("{}/{}", directory, LEVEL_DATA_FILE)) {
+            Ok(level) => return Ok(level),
+            Err(e) => e,
+        };
+        return match LevelData::read(&format!("{}/{}", directory, LEVEL_DATA_BACKUP_FILE)) {
+            Ok(level) => {
+                println!("{}, using the backup level data", error);
+                Ok(level)
+            }
+            Err(_) => Err(error),
+        };
+    }
+    fn read(filename: &str) -> Result<LevelData, VoxIoError> {
+        let level: LevelData = read_meta_chunk_from_file(filename)?;
+        level
+            .preset
+            .validate()
+            .map_err(|reason| VoxIoError::Corrupt(filename.to_string(), reason))?;
+        return Ok(level);
+    }
+    pub fn save(&mut self, directory: &str) -> Result<(), VoxIoError> {
+        std::fs::create_dir_all(directory).map_err(|e| VoxIoError::Io(directory.to_string(), e))?;
+        let filename = format!("{}/{}", directory, LEVEL_DATA_FILE);
+        let backup = format!("{}/{}", directory, LEVEL_DATA_BACKUP_FILE);
+        // an unreadable level data would replace a good backup, it is overwritten instead
+        match LevelData::read(&filename) {
+            Ok(_) => std::fs::rename(&filename, &backup).map_err(|e| VoxIoError::Io(filename.clone(), e))?,
+            Err(VoxIoError::NotFound(_)) => {}
+            Err(e) => println!("{}, keeping the backup level data", e),
+        }
+        self.format_version = FORMAT_VERSION;
+        return write_to_file(&filename, self);
+    }
+    // copies the parts of the running game that have to survive a restart
+    pub fn update(&mut self, world: &SmallWorld, player: &Player) {
+        self.time = world.time;
+        self.player_position = [player.position.x, player.position.y, player.position.z];
+        self.player_direction = player.direction();
+    }
+    pub fn apply_to_player(&self, player: &mut Player) {
+        player.position.x = self.player_position[0];
+        player.position.y = self.player_position[1];
+        player.position.z = self.player_position[2];
+        player.camera.look_in_direction(self.player_direction);
+    }
+}
@@ -1 +1,3 @@
-pub mod small_world;
\ No newline at end of file
+pub mod chunk_io_thread;
+pub mod level_data;
+pub mod small_world;
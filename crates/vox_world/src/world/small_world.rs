@@ -1,20 +1,32 @@
 use crate::blocks::block::{BlockId, get_blockid};
+use crate::physics::raycast::{raycast, RaycastHit};
 use crate::player::Player;
+use crate::world_gen::basic::ChunkGenerator;
 use crate::world_gen::chunk::Chunk;
-use crate::world_gen::meta_chunk::MetaChunk;
+use crate::world::level_data::LevelData;
+use crate::world_gen::meta_chunk::{MetaChunk, SAVE_CODEC, SAVE_DIR};
+use crate::world_gen::world_preset::WorldPreset;
 use rayon::prelude::ParallelSliceMut;
 use std::collections::{HashMap, HashSet};
 use std::time::Instant;
 use vox_core::constants::{METACHUNKSIZE, METACHUNK_GEN_RANGE};
-use vox_core::positions::{ChunkPos, GlobalBlockPos, MetaChunkPos};
+use vox_core::positions::{ChunkPos, GlobalBlockPos, MetaChunkPos, ObjectPos};
 use crate::blocks::block_type::BlockType;
+use vox_io::io::compression::CompressionStats;
 
 pub struct SmallWorld {
     chunks: Vec<(MetaChunkPos, MetaChunk)>,
     pub loading_chunks: HashSet<MetaChunkPos>,
     pub world_seed: u32,
+    pub preset: WorldPreset,
     pub time: f64,
     start_time: Instant,
+    // time the world had been played before it was opened
+    start_time_offset: f64,
+    // directory the metachunks are saved in
+    pub save_dir: String,
+    // totals over every metachunk saved since the world was opened
+    pub save_stats: CompressionStats,
 }
 
 impl SmallWorld {
@@ -62,8 +74,19 @@ impl SmallWorld {
         };
     }
 
+    // the first block along the ray within max_dist for which filter returns true
+    pub fn raycast(
+        &self,
+        origin: ObjectPos,
+        dir: [f32; 3],
+        max_dist: f32,
+        filter: impl Fn(BlockId) -> bool,
+    ) -> Option<RaycastHit> {
+        raycast(self, origin, dir, max_dist, filter)
+    }
+
     pub fn get_chunk_mut(&mut self, pos: &ChunkPos) -> Option<&mut Chunk<4,2, 8>> {
-        if pos.y >= METACHUNKSIZE as i32 {
+        if pos.y < 0 || pos.y >= METACHUNKSIZE as i32 {
             return None;
         }
         let c = self.get_meta_chunk_mut(&pos.get_meta_chunk_pos());
@@ -72,40 +95,96 @@ impl SmallWorld {
             None => None,
         };
     }
-    pub fn new(seed: u32) -> SmallWorld {
+    pub fn new(seed: u32, preset: WorldPreset) -> SmallWorld {
         SmallWorld {
             chunks: Vec::new(),
             loading_chunks: HashSet::new(),
             world_seed: seed,
+            preset,
             time: 0.0,
             start_time: Instant::now(),
+            start_time_offset: 0.0,
+            save_dir: format!("{}/{}", SAVE_DIR, seed),
+            save_stats: CompressionStats::new(SAVE_CODEC),
+        }
+    }
+    // continues a saved world, the metachunks are loaded from save_dir as they are requested
+    pub fn from_level(level: &LevelData, save_dir: &str) -> SmallWorld {
+        let mut world = SmallWorld::new(level.seed, level.preset.clone());
+        world.start_time_offset = level.time;
+        world.time = level.time;
+        world.save_dir = save_dir.to_string();
+        return world;
+    }
+    // loads or generates every metachunk the box between min and max overlaps, for tools that
+    // edit a world without a player walking around in it
+    pub fn load_area(&mut self, generator: &ChunkGenerator, min: &GlobalBlockPos, max: &GlobalBlockPos) {
+        let low = GlobalBlockPos { x: min.x.min(max.x), y: 0, z: min.z.min(max.z) }.get_meta_chunk_pos();
+        let high = GlobalBlockPos { x: min.x.max(max.x), y: 0, z: min.z.max(max.z) }.get_meta_chunk_pos();
+        for x in low.x..=high.x {
+            for z in low.z..=high.z {
+                let pos = MetaChunkPos { x, z };
+                if self.get_meta_chunk(&pos).is_none() {
+                    let chunk = MetaChunk::load_or_gen(pos, generator, &self.save_dir, false);
+                    self.add_chunk(pos, chunk);
+                }
+            }
         }
     }
     pub fn set_block(&mut self, block: u8, pos: GlobalBlockPos) {
-        match self.get_chunk_mut(&pos.get_chunk_pos()) {
-            Some(c) => c.set_block(block, &pos.get_local_pos()),
+        // goes through the metachunk so its heightmaps stay up to date
+        match self.get_meta_chunk_mut(&pos.get_meta_chunk_pos()) {
+            Some(c) => c.set_block(&pos, block),
             None => (),
         };
     }
 
-    pub fn filter_chunks(&mut self, player: &Player) {
-        self.chunks.retain(|(pos, _)| {
+    // removes the metachunks that are too far from the player, the modified ones are returned
+    // serialized so they can be saved
+    pub fn filter_chunks(&mut self, player: &Player) -> Vec<(MetaChunkPos, Vec<u8>)> {
+        let mut to_save = Vec::new();
+        let save_dir = &self.save_dir;
+        self.chunks.retain(|(pos, chunk)| {
             if MetaChunk::retain_meta_chunk(player, *pos) {
                 return true;
             }
+            if chunk.modified {
+                match chunk.serialize(save_dir) {
+                    Ok(raw) => to_save.push((*pos, raw)),
+                    Err(e) => println!("could not save chunk {:?}, {}", pos, e),
+                }
+            }
             println!("remove chunk: {:?}", pos);
             return false;
         });
+        return to_save;
+    }
+    // serializes every modified metachunk and marks it as saved
+    pub fn take_modified_chunks(&mut self) -> Vec<(MetaChunkPos, Vec<u8>)> {
+        let mut to_save = Vec::new();
+        for (pos, chunk) in self.chunks.iter_mut() {
+            if !chunk.modified {
+                continue;
+            }
+            match chunk.serialize(&self.save_dir) {
+                Ok(raw) => {
+                    to_save.push((*pos, raw));
+                    chunk.modified = false;
+                }
+                Err(e) => println!("could not save chunk {:?}, {}", pos, e),
+            }
+        }
+        return to_save;
     }
 
     pub fn update(&mut self) {
-        self.time = self.start_time.elapsed().as_secs_f64();
+        self.time = self.start_time_offset + self.start_time.elapsed().as_secs_f64();
     }
     pub fn get_all_chunks(&self) -> &Vec<(MetaChunkPos, MetaChunk)> {
         return &self.chunks;
     }
     pub fn get_chunk(&self, pos: &ChunkPos) -> Option<&Chunk<4,2, 8>> {
-        if pos.y >= METACHUNKSIZE as i32 {
+        if pos.y < 0 || pos.y >= METACHUNKSIZE as i32 {
             return None;
         }
         let c = self.get_meta_chunk(&pos.get_meta_chunk_pos());
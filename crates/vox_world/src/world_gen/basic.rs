@@ -1,41 +1,77 @@
-use crate::blocks::block::{get_blockid, get_blocktype, BlockId};
+use crate::blocks::block::{get_blockid, BlockId};
 use crate::blocks::block_type::BlockType;
-use crate::world_gen::chunk::Chunk;
+use crate::world_gen::generation_stage::GenerationStage;
+use crate::world_gen::meta_chunk::MetaChunk;
+use crate::world_gen::world_preset::{TerrainConfig, WorldPreset};
 use noise::{Fbm, MultiFractal, NoiseFn, Seedable};
-use std::cmp::max;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Instant;
 use vox_core::constants::{CHUNKSIZE, METACHUNKSIZE};
-use vox_core::positions::{ChunkPos, LocalBlockPos};
+use vox_core::positions::ChunkPos;
 use vox_core::utils::coord_to_array_indice;
 
 pub struct ChunkGenerator {
     pub noise: Fbm,
     pub seed: u32,
-    pub functions: Vec<fn(&ChunkGenerator, &ChunkPos, &mut Vec<BlockId>)>,
+    pub water_level: i32,
+    pub stages: Vec<(String, Box<dyn GenerationStage>)>,
+    timings: Mutex<HashMap<String, f32>>,
 }
 
 impl ChunkGenerator {
     pub fn new(seed: u32) -> ChunkGenerator {
-        let mut functions = Vec::new();
-        functions.push(generate_landmass as fn(&ChunkGenerator, &ChunkPos, &mut Vec<BlockId>));
-        functions.push(floodfill_water as fn(&ChunkGenerator, &ChunkPos, &mut Vec<BlockId>));
-        functions.push(plant_grass as fn(&ChunkGenerator, &ChunkPos, &mut Vec<BlockId>));
+        ChunkGenerator::from_preset(seed, &WorldPreset::default())
+    }
+    pub fn from_preset(seed: u32, preset: &WorldPreset) -> ChunkGenerator {
         ChunkGenerator {
-            noise: Fbm::new()
-                .set_seed(seed)
-                .set_octaves(3)
-                .set_persistence(0.6f64),
-            seed: 1,
-            functions,
+            noise: terrain_noise(seed, &preset.terrain),
+            seed,
+            water_level: preset.terrain.water_level,
+            stages: preset.build_stages(seed),
+            timings: Mutex::new(HashMap::new()),
         }
     }
     pub fn full_generation_pass(&self, pos: &ChunkPos) -> Vec<BlockId> {
         let mut chunk_data = generate_empty_chunk_data();
-        for f in &self.functions {
-            f(self, pos, &mut chunk_data);
+        for (name, stage) in &self.stages {
+            let timer = Instant::now();
+            stage.generate_chunk(self, pos, &mut chunk_data);
+            self.add_timing(name, timer.elapsed().as_secs_f32());
         }
         return chunk_data;
     }
+    pub fn decorate(&self, meta_chunk: &mut MetaChunk) {
+        for (name, stage) in &self.stages {
+            let timer = Instant::now();
+            stage.decorate(self, meta_chunk);
+            self.add_timing(name, timer.elapsed().as_secs_f32());
+        }
+    }
+    // returns the time spent per stage since the last call, in pipeline order
+    pub fn take_timings(&self) -> Vec<(String, f32)> {
+        let mut timings = self.timings.lock().unwrap();
+        let result = self
+            .stages
+            .iter()
+            .filter_map(|(name, _)| timings.get(name).map(|t| (name.clone(), *t)))
+            .collect();
+        timings.clear();
+        return result;
+    }
+    fn add_timing(&self, name: &str, seconds: f32) {
+        let mut timings = self.timings.lock().unwrap();
+        *timings.entry(name.to_string()).or_insert(0.0) += seconds;
+    }
+}
+
+fn terrain_noise(seed: u32, terrain: &TerrainConfig) -> Fbm {
+    Fbm::new()
+        .set_seed(seed)
+        .set_octaves(terrain.octaves)
+        .set_persistence(terrain.persistence)
 }
+
 pub fn generate_empty_chunk_data() -> Vec<BlockId> {
     let mut arr: Vec<BlockId> = Vec::with_capacity(CHUNKSIZE * CHUNKSIZE * CHUNKSIZE);
     for _ in 0..CHUNKSIZE as i32 {
@@ -48,36 +84,139 @@ pub fn generate_empty_chunk_data() -> Vec<BlockId> {
     return arr;
 }
 
-pub fn generate_landmass(chunk_generator: &ChunkGenerator, pos: &ChunkPos, chunk: &mut Vec<BlockId>) {
-    for x in 0..CHUNKSIZE as i32 {
-        for z in 0..CHUNKSIZE as i32 {
-            let height = get_xz_heigth(x, z, chunk_generator, pos);
-            let height = height - pos.y * CHUNKSIZE as i32;
-            for y in 0..height {
-                if y >= CHUNKSIZE as i32 {
-                    continue;
+pub struct LandmassStage;
+
+impl GenerationStage for LandmassStage {
+    fn generate_chunk(&self, chunk_generator: &ChunkGenerator, pos: &ChunkPos, chunk: &mut Vec<BlockId>) {
+        for x in 0..CHUNKSIZE as i32 {
+            for z in 0..CHUNKSIZE as i32 {
+                let height = get_xz_heigth(x, z, chunk_generator, pos);
+                let height = height - pos.y * CHUNKSIZE as i32;
+                for y in 0..height {
+                    if y >= CHUNKSIZE as i32 {
+                        continue;
+                    }
+                    chunk[coord_to_array_indice(x as u32,y as u32,z as u32, CHUNKSIZE as u32)] = get_blockid(BlockType::Stone);
                 }
-                chunk[coord_to_array_indice(x as u32,y as u32,z as u32, CHUNKSIZE as u32)] = get_blockid(BlockType::Stone);
             }
         }
     }
 }
-pub fn plant_grass(chunk_generator: &ChunkGenerator, pos: &ChunkPos, chunk: &mut Vec<BlockId>) {
-    for x in 0..CHUNKSIZE as i32 {
-        for z in 0..CHUNKSIZE as i32 {
-            let height = get_xz_heigth(x, z, chunk_generator, pos);
-            if height < (pos.y + 1) * CHUNKSIZE as i32 && height >= (pos.y) * CHUNKSIZE as i32 {
-                let y = height - pos.y * CHUNKSIZE as i32;
-                if height > (CHUNKSIZE as f32 * METACHUNKSIZE as f32 * 0.8) as i32 {
-                    continue;
+
+pub struct CaveStage {
+    pub cave_noise: Fbm,
+    pub worm_noise: (Fbm, Fbm),
+    pub cheese_scale: f64,
+    pub cheese_threshold: f64,
+    pub worm_scale: f64,
+    pub worm_width: f64,
+    // layers of rock that stay in place under shallow water so the sea does not drain into caves
+    pub seal_depth: i32,
+}
+
+impl CaveStage {
+    pub fn new(
+        seed: u32,
+        cheese_scale: f64,
+        cheese_threshold: f64,
+        worm_scale: f64,
+        worm_width: f64,
+        seal_depth: i32,
+    ) -> CaveStage {
+        CaveStage {
+            cave_noise: Fbm::new()
+                .set_seed(seed.wrapping_add(1))
+                .set_octaves(2)
+                .set_persistence(0.5f64),
+            worm_noise: (
+                Fbm::new().set_seed(seed.wrapping_add(2)).set_octaves(1),
+                Fbm::new().set_seed(seed.wrapping_add(3)).set_octaves(1),
+            ),
+            cheese_scale,
+            cheese_threshold,
+            worm_scale,
+            worm_width,
+            seal_depth,
+        }
+    }
+    fn is_cave(&self, global_pos: [f64; 3]) -> bool {
+        let cheese_pos = [
+            global_pos[0] / self.cheese_scale,
+            global_pos[1] / self.cheese_scale,
+            global_pos[2] / self.cheese_scale,
+        ];
+        if self.cave_noise.get(cheese_pos) > self.cheese_threshold {
+            return true;
+        }
+        // worm caves are the tunnels where two independent noise fields both cross zero
+        let worm_pos = [
+            global_pos[0] / self.worm_scale,
+            global_pos[1] / self.worm_scale,
+            global_pos[2] / self.worm_scale,
+        ];
+        return self.worm_noise.0.get(worm_pos).abs() < self.worm_width
+            && self.worm_noise.1.get(worm_pos).abs() < self.worm_width;
+    }
+}
+
+impl GenerationStage for CaveStage {
+    fn generate_chunk(&self, chunk_generator: &ChunkGenerator, pos: &ChunkPos, chunk: &mut Vec<BlockId>) {
+        for x in 0..CHUNKSIZE as i32 {
+            for z in 0..CHUNKSIZE as i32 {
+                let height = get_xz_heigth(x, z, chunk_generator, pos);
+                for y in 0..CHUNKSIZE as i32 {
+                    let global_y = y + pos.y * CHUNKSIZE as i32;
+                    // keep the bottom layer intact so caves never open into the void
+                    if global_y <= 0 || global_y >= height {
+                        continue;
+                    }
+                    if height <= chunk_generator.water_level + self.seal_depth
+                        && global_y >= height - self.seal_depth
+                    {
+                        continue;
+                    }
+                    let global_pos = [
+                        (x + pos.x * CHUNKSIZE as i32) as f64,
+                        global_y as f64,
+                        (z + pos.z * CHUNKSIZE as i32) as f64,
+                    ];
+                    if self.is_cave(global_pos) {
+                        chunk[coord_to_array_indice(x as u32,y as u32,z as u32, CHUNKSIZE as u32)] = get_blockid(BlockType::Air);
+                    }
                 }
-                chunk[coord_to_array_indice(x as u32,y as u32,z as u32, CHUNKSIZE as u32)] = get_blockid(BlockType::Grass);
             }
         }
     }
 }
 
-fn get_xz_heigth(x: i32, z: i32, chunk_generator: &ChunkGenerator, pos: &ChunkPos) -> i32 {
+pub struct GrassStage {
+    pub max_height: i32,
+}
+
+impl GenerationStage for GrassStage {
+    fn generate_chunk(&self, chunk_generator: &ChunkGenerator, pos: &ChunkPos, chunk: &mut Vec<BlockId>) {
+        for x in 0..CHUNKSIZE as i32 {
+            for z in 0..CHUNKSIZE as i32 {
+                let height = get_xz_heigth(x, z, chunk_generator, pos);
+                if height < (pos.y + 1) * CHUNKSIZE as i32 && height >= (pos.y) * CHUNKSIZE as i32 {
+                    let y = height - pos.y * CHUNKSIZE as i32;
+                    if height > self.max_height {
+                        continue;
+                    }
+                    if y > 0
+                        && chunk[coord_to_array_indice(x as u32,(y - 1) as u32,z as u32, CHUNKSIZE as u32)]
+                            == get_blockid(BlockType::Air)
+                    {
+                        continue;
+                    }
+                    chunk[coord_to_array_indice(x as u32,y as u32,z as u32, CHUNKSIZE as u32)] = get_blockid(BlockType::Grass);
+                }
+            }
+        }
+    }
+}
+
+pub fn get_xz_heigth(x: i32, z: i32, chunk_generator: &ChunkGenerator, pos: &ChunkPos) -> i32 {
     let noise = [
         (x + (pos.x * CHUNKSIZE as i32)) as f64 / (METACHUNKSIZE * CHUNKSIZE) as f64,
         (z + (pos.z * CHUNKSIZE as i32)) as f64 / (METACHUNKSIZE * CHUNKSIZE) as f64,
@@ -86,17 +225,23 @@ fn get_xz_heigth(x: i32, z: i32, chunk_generator: &ChunkGenerator, pos: &ChunkPo
         as i32
 }
 
-pub fn floodfill_water(_: &ChunkGenerator, pos: &ChunkPos, chunk: &mut Vec<BlockId>) {
-    for x in 0..CHUNKSIZE as i32 {
-        for z in 0..CHUNKSIZE as i32 {
-            for y in 0..CHUNKSIZE as i32 {
-                let water_level = CHUNKSIZE * METACHUNKSIZE / 3;
-                let global_y = (y as i32 + (pos.y * CHUNKSIZE as i32)) as f64;
-                if global_y < water_level as f64
-                    && chunk[coord_to_array_indice(x as u32,y as u32,z as u32, CHUNKSIZE as u32)]
-                        == get_blockid(BlockType::Air)
-                {
-                    chunk[coord_to_array_indice(x as u32,y as u32,z as u32, CHUNKSIZE as u32)] = get_blockid(BlockType::Water);
+pub struct WaterStage;
+
+impl GenerationStage for WaterStage {
+    fn generate_chunk(&self, chunk_generator: &ChunkGenerator, pos: &ChunkPos, chunk: &mut Vec<BlockId>) {
+        for x in 0..CHUNKSIZE as i32 {
+            for z in 0..CHUNKSIZE as i32 {
+                // only the open column above the terrain is flooded, air below it belongs to caves
+                let height = get_xz_heigth(x, z, chunk_generator, pos);
+                for y in 0..CHUNKSIZE as i32 {
+                    let global_y = y as i32 + (pos.y * CHUNKSIZE as i32);
+                    if global_y < chunk_generator.water_level
+                        && global_y >= height
+                        && chunk[coord_to_array_indice(x as u32,y as u32,z as u32, CHUNKSIZE as u32)]
+                            == get_blockid(BlockType::Air)
+                    {
+                        chunk[coord_to_array_indice(x as u32,y as u32,z as u32, CHUNKSIZE as u32)] = get_blockid(BlockType::Water);
+                    }
                 }
             }
         }
@@ -3,13 +3,14 @@ use crate::blocks::block_type::BlockType;
 use crate::world_gen::basic::ChunkGenerator;
 use arrayvec::ArrayVec;
 use bytemuck::{Pod, Zeroable};
-use serde::{Deserialize, Serialize};
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_big_array::big_array;
 use vox_core::constants::CHUNKSIZE;
 use vox_core::positions::{ChunkPos, LocalBlockPos};
 use vox_core::utils::coord_to_array_indice;
 
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
 enum BlockIdOrPointer {
     Id(BlockId),
     Ptr(u32),
@@ -24,6 +25,50 @@ pub struct Chunk<const Depth: usize, const BlockSize: usize, const BlockSizeCube
     data_structure: [Vec<[BlockIdOrPointer; BlockSizeCubed]>; Depth], //array of layers where every layer has a vector with sparse data, and all sparse data has a ptr to its owner in the layer above
 }
 
+// the layers are stored flattened since serde can't derive for const generic array sizes
+#[derive(Serialize, Deserialize)]
+struct ChunkLayers {
+    layers: Vec<Vec<BlockIdOrPointer>>,
+}
+
+impl<const Depth: usize, const BlockSize: usize, const BlockSizeCubed: usize> Serialize
+    for Chunk<Depth, BlockSize, BlockSizeCubed>
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let layers = self
+            .data_structure
+            .iter()
+            .map(|layer| layer.iter().flatten().copied().collect())
+            .collect();
+        ChunkLayers { layers }.serialize(serializer)
+    }
+}
+
+impl<'de, const Depth: usize, const BlockSize: usize, const BlockSizeCubed: usize> Deserialize<'de>
+    for Chunk<Depth, BlockSize, BlockSizeCubed>
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let data = ChunkLayers::deserialize(deserializer)?;
+        if data.layers.len() != Depth
+            || data.layers.iter().any(|layer| layer.len() % BlockSizeCubed != 0)
+            || data.layers[0].len() != BlockSizeCubed
+        {
+            return Err(D::Error::custom("chunk layers do not match the chunk dimensions"));
+        }
+        let data_structure = array_init::array_init(|i| {
+            data.layers[i]
+                .chunks_exact(BlockSizeCubed)
+                .map(|brick| {
+                    let mut b = [BlockIdOrPointer::Id(0); BlockSizeCubed];
+                    b.copy_from_slice(brick);
+                    b
+                })
+                .collect()
+        });
+        return Ok(Self { data_structure });
+    }
+}
+
 impl<const Depth: usize, const BlockSize: usize, const BlockSizeCubed: usize>
     Chunk<Depth, BlockSize, BlockSizeCubed>
 {
@@ -40,15 +85,39 @@ impl<const Depth: usize, const BlockSize: usize, const BlockSizeCubed: usize>
     pub fn generate(chunk_generator: &ChunkGenerator, pos: &ChunkPos) -> Self {
         debug_assert!(BlockSize == 4 || BlockSize == 2 || BlockSize == 8);
         debug_assert!(BlockSize.pow(3) == BlockSizeCubed);
+        let data = chunk_generator.full_generation_pass(pos);
+        // start from the most common block so only the remaining blocks have to be set one by one
+        let mut counts = [0u32; BlockId::MAX as usize + 1];
+        for block in data.iter() {
+            counts[*block as usize] += 1;
+        }
+        let base = (0..counts.len()).max_by_key(|b| counts[*b]).unwrap() as BlockId;
         let mut c = Self {
             data_structure: array_init::array_init(|i| {
                 return if i == 0 {
-                    vec![[BlockIdOrPointer::Id(get_blockid(BlockType::Air)); BlockSizeCubed]; 1]
+                    vec![[BlockIdOrPointer::Id(base); BlockSizeCubed]; 1]
                 } else {
                     Vec::new()
                 };
             }),
         };
+        for x in 0..CHUNKSIZE as u32 {
+            for y in 0..CHUNKSIZE as u32 {
+                for z in 0..CHUNKSIZE as u32 {
+                    let block = data[coord_to_array_indice(x, y, z, CHUNKSIZE as u32)];
+                    if block != base {
+                        c.set_block(
+                            block,
+                            &LocalBlockPos {
+                                x: x as i32,
+                                y: y as i32,
+                                z: z as i32,
+                            },
+                        );
+                    }
+                }
+            }
+        }
 
         return c;
     }
@@ -133,13 +202,12 @@ impl<const Depth: usize, const BlockSize: usize, const BlockSizeCubed: usize>
     fn fix_tree_upwards(&mut self, depth: usize, ptrs: [usize; Depth], pos: &LocalBlockPos) {
         for j in (1..depth + 1).rev() {
             //iterate back up to find complete chunks
-            self.print_structured();
-            if self.data_structure[j][ptrs[depth]]
+            if self.data_structure[j][ptrs[j]]
                 .iter()
-                .all(|x| *x == self.data_structure[j][ptrs[depth]][0])
+                .all(|x| *x == self.data_structure[j][ptrs[j]][0])
             {
                 //if all chunks in layer j are the same
-                if let BlockIdOrPointer::Id(b) = self.data_structure[j][ptrs[depth]][0] {
+                if let BlockIdOrPointer::Id(b) = self.data_structure[j][ptrs[j]][0] {
                     //get the homogenious block
                     let parent_ptr = ptrs[j - 1];
                     self.data_structure[j].remove(ptrs[j]); //remove the entire chunk from layer
@@ -151,7 +219,7 @@ impl<const Depth: usize, const BlockSize: usize, const BlockSizeCubed: usize>
                             if let BlockIdOrPointer::Ptr(x) =
                                 self.data_structure[j - 1][brick_i][possible_pointer]
                             {
-                                if x > ptrs[depth] as u32 {
+                                if x > ptrs[j] as u32 {
                                     self.data_structure[j - 1][brick_i][possible_pointer] =
                                         BlockIdOrPointer::Ptr(x - 1);
                                 }
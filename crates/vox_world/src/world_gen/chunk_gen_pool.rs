@@ -0,0 +1,278 @@
+use crate::world_gen::basic::ChunkGenerator;
+use crate::world_gen::meta_chunk::MetaChunk;
+use crate::world_gen::world_preset::WorldPreset;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashSet};
+use std::panic;
+use std::panic::AssertUnwindSafe;
+use std::sync::mpsc;
+use std::sync::mpsc::{Receiver, RecvError, Sender, TryRecvError};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::thread::JoinHandle;
+use std::time::Instant;
+use vox_core::positions::MetaChunkPos;
+
+struct GenRequest {
+    pos: MetaChunkPos,
+    seed: u32,
+    distance: i32,
+    // requests at the same distance are handled in the order they came in
+    order: u64,
+    requested_at: Instant,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ChunkGenMetrics {
+    pub queue_depth: usize,
+    pub in_progress: usize,
+    pub generated: u64,
+    pub cancelled: u64,
+    // generations that panicked, their positions are not generated
+    pub failed: u64,
+    // seconds between the request and a worker picking it up
+    pub average_queue_latency: f32,
+    pub max_queue_latency: f32,
+    // seconds between the request and the finished metachunk
+    pub average_total_latency: f32,
+}
+
+struct PoolState {
+    queue: BinaryHeap<GenRequest>,
+    queued: HashSet<MetaChunkPos>,
+    center: MetaChunkPos,
+    next_order: u64,
+    // workers take no new requests while paused
+    paused: bool,
+    shutdown: bool,
+    metrics: ChunkGenMetrics,
+    total_queue_latency: f32,
+    total_latency: f32,
+}
+
+pub struct ChunkGenPool {
+    state: Arc<(Mutex<PoolState>, Condvar)>,
+    pub chunk_generator_receiver: Receiver<(MetaChunk, MetaChunkPos)>,
+    pub workers: Vec<JoinHandle<()>>,
+}
+
+impl ChunkGenPool {
+    pub fn new(preset: WorldPreset, worker_count: usize) -> ChunkGenPool {
+        let state = Arc::new((
+            Mutex::new(PoolState {
+                queue: BinaryHeap::new(),
+                queued: HashSet::new(),
+                center: MetaChunkPos { x: 0, z: 0 },
+                next_order: 0,
+                paused: false,
+                shutdown: false,
+                metrics: ChunkGenMetrics::default(),
+                total_queue_latency: 0.0,
+                total_latency: 0.0,
+            }),
+            Condvar::new(),
+        ));
+        let (gen_chunk_request_done, gen_chunk_receiver_done) = mpsc::channel();
+        let workers = (0..worker_count.max(1))
+            .map(|i| {
+                let state = state.clone();
+                let sender = gen_chunk_request_done.clone();
+                let preset = preset.clone();
+                thread::Builder::new()
+                    .name(format!("chunk gen worker {}", i))
+                    .spawn(move || worker_loop(state, sender, preset))
+                    .unwrap()
+            })
+            .collect();
+        return ChunkGenPool {
+            state,
+            chunk_generator_receiver: gen_chunk_receiver_done,
+            workers,
+        };
+    }
+    // leaves one core free for the render thread
+    pub fn default_worker_count() -> usize {
+        thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(2)
+            .saturating_sub(1)
+            .max(1)
+    }
+    pub fn request(&self, pos: MetaChunkPos, seed: u32) {
+        let (lock, condvar) = &*self.state;
+        let mut state = lock.lock().unwrap();
+        if !state.queued.insert(pos) {
+            return;
+        }
+        let order = state.next_order;
+        state.next_order += 1;
+        let distance = distance_squared(&pos, &state.center);
+        state.queue.push(GenRequest {
+            pos,
+            seed,
+            distance,
+            order,
+            requested_at: Instant::now(),
+        });
+        state.metrics.queue_depth = state.queue.len();
+        condvar.notify_one();
+    }
+    // reorders the queue around the new player position and cancels every request outside of the
+    // load radius, the cancelled positions are returned so they can be requested again later
+    pub fn update_center(&self, center: MetaChunkPos, load_radius: i32) -> Vec<MetaChunkPos> {
+        let (lock, _) = &*self.state;
+        let mut state = lock.lock().unwrap();
+        state.center = center;
+        let mut cancelled = Vec::new();
+        let mut kept = Vec::with_capacity(state.queue.len());
+        for mut request in std::mem::take(&mut state.queue).into_vec() {
+            if (request.pos.x - center.x).abs() > load_radius || (request.pos.z - center.z).abs() > load_radius {
+                cancelled.push(request.pos);
+            } else {
+                request.distance = distance_squared(&request.pos, &center);
+                kept.push(request);
+            }
+        }
+        for pos in &cancelled {
+            state.queued.remove(pos);
+        }
+        state.queue = BinaryHeap::from(kept);
+        state.metrics.cancelled += cancelled.len() as u64;
+        state.metrics.queue_depth = state.queue.len();
+        return cancelled;
+    }
+    // the workers finish the metachunks they are generating and wait for resume, requests are
+    // still queued and cancelled in the meantime
+    pub fn pause(&self) {
+        let (lock, _) = &*self.state;
+        lock.lock().unwrap().paused = true;
+    }
+    pub fn resume(&self) {
+        let (lock, condvar) = &*self.state;
+        lock.lock().unwrap().paused = false;
+        condvar.notify_all();
+    }
+    pub fn metrics(&self) -> ChunkGenMetrics {
+        let (lock, _) = &*self.state;
+        lock.lock().unwrap().metrics
+    }
+    pub fn get(&self) -> Result<(MetaChunk, MetaChunkPos), TryRecvError> {
+        self.chunk_generator_receiver.try_recv()
+    }
+    // blocks until a worker finishes a metachunk
+    pub fn get_blocking(&self) -> Result<(MetaChunk, MetaChunkPos), RecvError> {
+        self.chunk_generator_receiver.recv()
+    }
+}
+
+impl Drop for ChunkGenPool {
+    fn drop(&mut self) {
+        {
+            let (lock, condvar) = &*self.state;
+            lock.lock().unwrap().shutdown = true;
+            condvar.notify_all();
+        }
+        for worker in self.workers.drain(..) {
+            if worker.join().is_err() {
+                println!("chunk gen worker panicked");
+            }
+        }
+    }
+}
+
+fn worker_loop(
+    state: Arc<(Mutex<PoolState>, Condvar)>,
+    sender: Sender<(MetaChunk, MetaChunkPos)>,
+    preset: WorldPreset,
+) {
+    let (lock, condvar) = &*state;
+    let mut chunk_generator: Option<ChunkGenerator> = None;
+    loop {
+        let request = {
+            let mut state = lock.lock().unwrap();
+            while (state.queue.is_empty() || state.paused) && !state.shutdown {
+                state = condvar.wait(state).unwrap();
+            }
+            if state.shutdown {
+                return;
+            }
+            let request = state.queue.pop().unwrap();
+            let queue_latency = request.requested_at.elapsed().as_secs_f32();
+            state.total_queue_latency += queue_latency;
+            state.metrics.max_queue_latency = state.metrics.max_queue_latency.max(queue_latency);
+            state.metrics.queue_depth = state.queue.len();
+            state.metrics.in_progress += 1;
+            request
+        };
+
+        let timer = Instant::now();
+        println!("started generation for {:?}", request.pos);
+        if chunk_generator.as_ref().map_or(true, |g| g.seed != request.seed) {
+            chunk_generator = Some(ChunkGenerator::from_preset(request.seed, &preset));
+        }
+        let generator = chunk_generator.as_ref().unwrap();
+        let meta_chunk = match panic::catch_unwind(AssertUnwindSafe(|| MetaChunk::generate(request.pos, generator))) {
+            Ok(meta_chunk) => meta_chunk,
+            Err(_) => {
+                // the position is not requested again by itself, it would panic again
+                println!("generation for {:?} panicked, the metachunk is not loaded", request.pos);
+                chunk_generator = None;
+                let mut state = lock.lock().unwrap();
+                state.queued.remove(&request.pos);
+                state.metrics.in_progress -= 1;
+                state.metrics.failed += 1;
+                continue;
+            }
+        };
+        println!(
+            "done generation for: {:?} in {} sec",
+            request.pos,
+            timer.elapsed().as_secs_f32()
+        );
+        for (stage, time) in generator.take_timings() {
+            println!("    stage {}: {} sec", stage, time);
+        }
+
+        {
+            let mut state = lock.lock().unwrap();
+            state.queued.remove(&request.pos);
+            state.metrics.in_progress -= 1;
+            state.metrics.generated += 1;
+            state.total_latency += request.requested_at.elapsed().as_secs_f32();
+            state.metrics.average_queue_latency = state.total_queue_latency / state.metrics.generated as f32;
+            state.metrics.average_total_latency = state.total_latency / state.metrics.generated as f32;
+        }
+        if let Err(e) = sender.send((meta_chunk, request.pos)) {
+            println!("error while sending generated chunk: {}", e);
+            return;
+        }
+    }
+}
+
+fn distance_squared(a: &MetaChunkPos, b: &MetaChunkPos) -> i32 {
+    (a.x - b.x).pow(2) + (a.z - b.z).pow(2)
+}
+
+// the closest request has the highest priority in the max heap
+impl Ord for GenRequest {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .distance
+            .cmp(&self.distance)
+            .then_with(|| other.order.cmp(&self.order))
+    }
+}
+
+impl PartialOrd for GenRequest {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for GenRequest {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for GenRequest {}
@@ -0,0 +1,111 @@
+use crate::blocks::block::{get_blocktype, is_fluid, is_solid, BlockId};
+use crate::blocks::block_type::BlockType;
+use serde::{Deserialize, Serialize};
+use vox_core::constants::{CHUNKSIZE, METACHUNKSIZE};
+
+const COLUMNS: usize = CHUNKSIZE * METACHUNKSIZE;
+const WORLD_HEIGHT: i32 = (CHUNKSIZE * METACHUNKSIZE) as i32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum HeightmapKind {
+    // highest block that is solid or a fluid
+    MotionBlocking,
+    // highest solid block, ignoring fluids
+    OceanFloor,
+    // highest block that is not air
+    Surface,
+}
+
+impl HeightmapKind {
+    pub const ALL: [HeightmapKind; 3] = [
+        HeightmapKind::MotionBlocking,
+        HeightmapKind::OceanFloor,
+        HeightmapKind::Surface,
+    ];
+    pub fn counts(&self, block: BlockId) -> bool {
+        match self {
+            HeightmapKind::MotionBlocking => is_solid(block) || is_fluid(block),
+            HeightmapKind::OceanFloor => is_solid(block),
+            HeightmapKind::Surface => {
+                let block_type = get_blocktype(block);
+                block_type != BlockType::Air && block_type != BlockType::Unknown
+            }
+        }
+    }
+}
+
+// the y of the highest counted block for every column of a metachunk, -1 for columns without one
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ColumnHeightmaps {
+    motion_blocking: Vec<i16>,
+    ocean_floor: Vec<i16>,
+    surface: Vec<i16>,
+}
+
+impl ColumnHeightmaps {
+    // x and z are local to the metachunk, get_block gets local x, y and z as well
+    pub fn build(get_block: impl Fn(i32, i32, i32) -> BlockId) -> ColumnHeightmaps {
+        let mut heightmaps = ColumnHeightmaps {
+            motion_blocking: vec![-1; COLUMNS * COLUMNS],
+            ocean_floor: vec![-1; COLUMNS * COLUMNS],
+            surface: vec![-1; COLUMNS * COLUMNS],
+        };
+        for x in 0..COLUMNS as i32 {
+            for z in 0..COLUMNS as i32 {
+                for kind in HeightmapKind::ALL.iter() {
+                    let height = scan_down(*kind, x, WORLD_HEIGHT - 1, z, &get_block);
+                    heightmaps.get_map_mut(*kind)[index(x, z)] = height as i16;
+                }
+            }
+        }
+        return heightmaps;
+    }
+    pub fn get(&self, kind: HeightmapKind, x: i32, z: i32) -> i32 {
+        self.get_map(kind)[index(x, z)] as i32
+    }
+    // keeps the maps up to date after `block` was placed at the local position,
+    // get_block should already return the new block
+    pub fn on_block_set(&mut self, x: i32, y: i32, z: i32, block: BlockId, get_block: impl Fn(i32, i32, i32) -> BlockId) {
+        for kind in HeightmapKind::ALL.iter() {
+            let current = self.get(*kind, x, z);
+            if kind.counts(block) {
+                if y > current {
+                    self.get_map_mut(*kind)[index(x, z)] = y as i16;
+                }
+            } else if y == current {
+                let height = scan_down(*kind, x, y - 1, z, &get_block);
+                self.get_map_mut(*kind)[index(x, z)] = height as i16;
+            }
+        }
+    }
+    fn get_map(&self, kind: HeightmapKind) -> &Vec<i16> {
+        match kind {
+            HeightmapKind::MotionBlocking => &self.motion_blocking,
+            HeightmapKind::OceanFloor => &self.ocean_floor,
+            HeightmapKind::Surface => &self.surface,
+        }
+    }
+    fn get_map_mut(&mut self, kind: HeightmapKind) -> &mut Vec<i16> {
+        match kind {
+            HeightmapKind::MotionBlocking => &mut self.motion_blocking,
+            HeightmapKind::OceanFloor => &mut self.ocean_floor,
+            HeightmapKind::Surface => &mut self.surface,
+        }
+    }
+}
+
+fn scan_down(kind: HeightmapKind, x: i32, from_y: i32, z: i32, get_block: &impl Fn(i32, i32, i32) -> BlockId) -> i32 {
+    let mut y = from_y;
+    while y >= 0 {
+        if kind.counts(get_block(x, y, z)) {
+            return y;
+        }
+        y -= 1;
+    }
+    return -1;
+}
+
+#[inline]
+fn index(x: i32, z: i32) -> usize {
+    (x + z * COLUMNS as i32) as usize
+}
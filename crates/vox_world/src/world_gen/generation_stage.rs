@@ -0,0 +1,12 @@
+use crate::blocks::block::BlockId;
+use crate::world_gen::basic::ChunkGenerator;
+use crate::world_gen::meta_chunk::MetaChunk;
+use vox_core::positions::ChunkPos;
+
+// a single step of the world generation pipeline, stages run in the order of the world preset
+pub trait GenerationStage: Send + Sync {
+    // fills or changes the blocks of a single chunk, only sees the chunk itself
+    fn generate_chunk(&self, _chunk_generator: &ChunkGenerator, _pos: &ChunkPos, _chunk: &mut Vec<BlockId>) {}
+    // places features that can cross chunk borders once the whole meta chunk exists
+    fn decorate(&self, _chunk_generator: &ChunkGenerator, _meta_chunk: &mut MetaChunk) {}
+}
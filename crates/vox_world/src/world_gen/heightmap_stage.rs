@@ -0,0 +1,207 @@
+use crate::blocks::block::{get_blockid, BlockId};
+use crate::blocks::block_type::BlockType;
+use crate::world_gen::basic::ChunkGenerator;
+use crate::world_gen::generation_stage::GenerationStage;
+use serde::{Deserialize, Serialize};
+use vox_core::constants::{CHUNKSIZE, METACHUNKSIZE};
+use vox_core::positions::ChunkPos;
+use vox_core::utils::{coord_to_array_indice, wrap};
+use vox_io::io::image_reader::{read_png_from_file, Image};
+
+// what happens with positions outside of the image
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum EdgeMode {
+    Tile,
+    Clamp,
+}
+
+// columns whose material map pixel is closest to `color` get this surface
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SurfaceRule {
+    pub color: [u8; 3],
+    pub surface: BlockType,
+    pub subsurface: BlockType,
+    pub depth: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeightmapConfig {
+    pub heightmap: String,
+    pub material_map: Option<String>,
+    pub edge_mode: EdgeMode,
+    // amount of blocks covered by a single pixel in x and z
+    pub horizontal_scale: u32,
+    // height in blocks of a white pixel above base_height
+    pub vertical_scale: f32,
+    pub base_height: i32,
+    pub sea_level: i32,
+    pub rules: Vec<SurfaceRule>,
+    pub default_rule: SurfaceRule,
+    pub underwater_rule: SurfaceRule,
+}
+
+pub struct HeightmapStage {
+    pub config: HeightmapConfig,
+    pub heightmap: Option<Image>,
+    pub material_map: Option<Image>,
+}
+
+impl HeightmapConfig {
+    pub fn new(heightmap: &str, material_map: Option<&str>) -> HeightmapConfig {
+        let world_height = (CHUNKSIZE * METACHUNKSIZE) as i32;
+        HeightmapConfig {
+            heightmap: heightmap.to_string(),
+            material_map: material_map.map(|s| s.to_string()),
+            edge_mode: EdgeMode::Tile,
+            horizontal_scale: 1,
+            vertical_scale: (world_height - 8) as f32,
+            base_height: 4,
+            sea_level: world_height / 3,
+            rules: vec![
+                SurfaceRule {
+                    color: [0, 255, 0],
+                    surface: BlockType::Grass,
+                    subsurface: BlockType::Dirt,
+                    depth: 3,
+                },
+                SurfaceRule {
+                    color: [255, 255, 0],
+                    surface: BlockType::Sand,
+                    subsurface: BlockType::Sand,
+                    depth: 4,
+                },
+                SurfaceRule {
+                    color: [128, 128, 128],
+                    surface: BlockType::Stone,
+                    subsurface: BlockType::Stone,
+                    depth: 1,
+                },
+            ],
+            default_rule: SurfaceRule {
+                color: [0, 0, 0],
+                surface: BlockType::Grass,
+                subsurface: BlockType::Dirt,
+                depth: 3,
+            },
+            underwater_rule: SurfaceRule {
+                color: [0, 0, 0],
+                surface: BlockType::Sand,
+                subsurface: BlockType::Sand,
+                depth: 2,
+            },
+        }
+    }
+    // every worker reads the images again, so a missing or broken image has to be caught before the world is created
+    pub fn validate(&self) -> Result<(), String> {
+        read_png_from_file(&self.heightmap).map_err(|e| format!("could not load heightmap: {}", e))?;
+        if let Some(filename) = &self.material_map {
+            read_png_from_file(filename).map_err(|e| format!("could not load material map: {}", e))?;
+        }
+        return Ok(());
+    }
+}
+
+impl HeightmapStage {
+    pub fn new(config: HeightmapConfig) -> HeightmapStage {
+        let heightmap = match read_png_from_file(&config.heightmap) {
+            Ok(image) => Some(image),
+            Err(e) => {
+                println!("could not load heightmap: {}, generating flat terrain", e);
+                None
+            }
+        };
+        let material_map = match &config.material_map {
+            Some(filename) => match read_png_from_file(filename) {
+                Ok(image) => Some(image),
+                Err(e) => {
+                    println!("could not load material map: {}, using default surface rules", e);
+                    None
+                }
+            },
+            None => None,
+        };
+        HeightmapStage {
+            config,
+            heightmap,
+            material_map,
+        }
+    }
+    fn to_pixel(&self, global: i32, size: u32) -> u32 {
+        let pixel = global.div_euclid(self.config.horizontal_scale.max(1) as i32);
+        match self.config.edge_mode {
+            EdgeMode::Tile => wrap(pixel, size as i32) as u32,
+            EdgeMode::Clamp => pixel.clamp(0, size as i32 - 1) as u32,
+        }
+    }
+    pub fn get_height(&self, global_x: i32, global_z: i32) -> i32 {
+        let world_height = (CHUNKSIZE * METACHUNKSIZE) as i32;
+        let value = match &self.heightmap {
+            Some(image) => {
+                let pixel = image.get_pixel(
+                    self.to_pixel(global_x, image.width),
+                    self.to_pixel(global_z, image.height),
+                );
+                pixel[0] * 0.299 + pixel[1] * 0.587 + pixel[2] * 0.114
+            }
+            None => 0.0,
+        };
+        let height = self.config.base_height + (value * self.config.vertical_scale) as i32;
+        return height.clamp(1, world_height - 1);
+    }
+    pub fn get_rule(&self, global_x: i32, global_z: i32, height: i32) -> &SurfaceRule {
+        if height < self.config.sea_level {
+            return &self.config.underwater_rule;
+        }
+        let image = match &self.material_map {
+            Some(image) => image,
+            None => return &self.config.default_rule,
+        };
+        // the material map may have a different resolution, so it is stretched over the heightmap
+        let (width, depth) = match &self.heightmap {
+            Some(heightmap) => (heightmap.width, heightmap.height),
+            None => (image.width, image.height),
+        };
+        let x = self.to_pixel(global_x, width) * image.width / width;
+        let z = self.to_pixel(global_z, depth) * image.height / depth;
+        let pixel = image.get_pixel(x, z);
+        let color = [pixel[0] * 255.0, pixel[1] * 255.0, pixel[2] * 255.0];
+        let mut closest = &self.config.default_rule;
+        let mut closest_distance = f32::MAX;
+        for rule in &self.config.rules {
+            let distance: f32 = (0..3).map(|i| (rule.color[i] as f32 - color[i]).powi(2)).sum();
+            if distance < closest_distance {
+                closest = rule;
+                closest_distance = distance;
+            }
+        }
+        return closest;
+    }
+}
+
+impl GenerationStage for HeightmapStage {
+    fn generate_chunk(&self, _: &ChunkGenerator, pos: &ChunkPos, chunk: &mut Vec<BlockId>) {
+        for x in 0..CHUNKSIZE as i32 {
+            for z in 0..CHUNKSIZE as i32 {
+                let global_x = x + pos.x * CHUNKSIZE as i32;
+                let global_z = z + pos.z * CHUNKSIZE as i32;
+                let height = self.get_height(global_x, global_z);
+                let rule = self.get_rule(global_x, global_z, height);
+                for y in 0..CHUNKSIZE as i32 {
+                    let global_y = y + pos.y * CHUNKSIZE as i32;
+                    let block = if global_y == height {
+                        rule.surface
+                    } else if global_y < height && global_y >= height - rule.depth as i32 {
+                        rule.subsurface
+                    } else if global_y < height {
+                        BlockType::Stone
+                    } else if global_y < self.config.sea_level {
+                        BlockType::Water
+                    } else {
+                        continue;
+                    };
+                    chunk[coord_to_array_indice(x as u32, y as u32, z as u32, CHUNKSIZE as u32)] = get_blockid(block);
+                }
+            }
+        }
+    }
+}
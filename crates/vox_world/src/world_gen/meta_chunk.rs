@@ -1,33 +1,72 @@
-use crate::algorithms::bfs_world::bfs_world_air;
-
-use crate::blocks::block::{get_blockid, get_blocktype, BlockId};
+use crate::blocks::block::{get_blockid, BlockId};
 use crate::blocks::block_type::BlockType;
 use crate::player::Player;
-use crate::structures::square::place_square;
-use crate::structures::tree::place_tree;
 use crate::world_gen::basic::ChunkGenerator;
 use crate::world_gen::chunk::Chunk;
-use rand::distributions::{Distribution, Standard, Uniform};
+use crate::world_gen::column_heightmaps::{ColumnHeightmaps, HeightmapKind};
+use rand::distributions::{Distribution, Standard};
 use rand::prelude::*;
-use rand_distr::Normal;
 use serde::{Deserialize, Serialize};
 use std::borrow::BorrowMut;
 use vox_core::constants::{CHUNKSIZE, METACHUNKSIZE, METACHUNK_GEN_RANGE};
 use vox_core::positions::{ChunkPos, GlobalBlockPos, LocalChunkPos, MetaChunkPos};
 use vox_core::utils::{to_sign_of, wrap};
-use vox_io::io::file_reader::read_meta_chunk_from_file;
-use vox_io::io::file_writer::write_to_file;
+use vox_io::io::compression::{Codec, CompressionStats};
+use vox_io::io::error::VoxIoError;
+use vox_io::io::file_reader::{deserialize_serialized, read_meta_chunk_from_file};
+use vox_io::io::file_writer::{serialize_for_file, write_serialized_to_file};
+use vox_io::io::save_format::quarantine_file;
+
+// every world gets its own directory in here
+pub const SAVE_DIR: &str = "saves";
+// chunks are saved while playing, so favour speed over size
+pub const SAVE_CODEC: Codec = Codec::Lz4;
 
+#[derive(Serialize, Deserialize)]
 pub struct MetaChunk {
     chunks: Vec<Chunk<4, 2, 8>>,
     pub pos: MetaChunkPos,
     pub seed: u32,
+    heightmaps: ColumnHeightmaps,
+    // set when a block changes after generation, only modified metachunks are written to disk
+    #[serde(skip)]
+    pub modified: bool,
 }
 
 impl MetaChunk {
-    pub fn load_or_gen(pos: MetaChunkPos, seed: u32, force_gen: bool) -> MetaChunk {
-        let chunk_generator = ChunkGenerator::new(seed);
-
+    pub fn load_or_gen(
+        pos: MetaChunkPos,
+        chunk_generator: &ChunkGenerator,
+        save_dir: &str,
+        force_gen: bool,
+    ) -> MetaChunk {
+        if !force_gen {
+            if let Some(chunk) = MetaChunk::load_saved(save_dir, pos, chunk_generator.seed) {
+                return chunk;
+            }
+        }
+        return MetaChunk::generate(pos, chunk_generator);
+    }
+    // returns None when the metachunk has to be generated, either because it was never saved or
+    // because the save can't be used
+    pub fn load_saved(save_dir: &str, pos: MetaChunkPos, seed: u32) -> Option<MetaChunk> {
+        match MetaChunk::load(save_dir, pos, seed) {
+            Ok(chunk) => return Some(chunk),
+            Err(VoxIoError::NotFound(_)) => {}
+            // keep the unreadable file around instead of overwriting it on the next save
+            Err(e @ VoxIoError::Corrupt(..)) | Err(e @ VoxIoError::VersionMismatch { .. }) => {
+                match quarantine_file(e.filename()) {
+                    Ok(target) => println!("{}, moved it to {} and regenerating metachunk {:?}", e, target, pos),
+                    Err(q) => println!("{}, regenerating metachunk {:?}, could not quarantine it: {}", e, pos, q),
+                }
+            }
+            Err(e @ VoxIoError::Io(..)) => {
+                println!("could not read saved metachunk, {}, generating it instead", e);
+            }
+        }
+        return None;
+    }
+    pub fn generate(pos: MetaChunkPos, chunk_generator: &ChunkGenerator) -> MetaChunk {
         let mut chunks: Vec<Chunk<4,2,8>> =
             Vec::with_capacity(METACHUNKSIZE * METACHUNKSIZE * METACHUNKSIZE);
         for z in 0..METACHUNKSIZE {
@@ -38,106 +77,103 @@ impl MetaChunk {
                         y: y as i32,
                         z: z as i32 + pos.z * METACHUNKSIZE as i32,
                     };
-                    chunks.push(Chunk::generate(&chunk_generator, local_pos));
+                    chunks.push(Chunk::generate(chunk_generator, local_pos));
                 }
             }
         }
 
-        let mut chunk = MetaChunk { pos, chunks, seed };
-
-        let structure_x = pos.x * METACHUNKSIZE as i32 * CHUNKSIZE as i32 + 20;
-        let structure_z = pos.z * METACHUNKSIZE as i32 * CHUNKSIZE as i32 + 20;
-        let structure_y = chunk.first_above_land_y(structure_x, structure_z);
-        let global_center_pos = GlobalBlockPos {
-            x: structure_x,
-            y: structure_y,
-            z: structure_z,
-        };
-        bfs_world_air(
-            &global_center_pos,
-            5,
-            &mut chunk,
-            get_blockid(BlockType::Sand),
-        );
-
-        let structure_x = pos.x * METACHUNKSIZE as i32 * CHUNKSIZE as i32 + 3;
-        let structure_z = pos.z * METACHUNKSIZE as i32 * CHUNKSIZE as i32 + 60;
-        let structure_y = chunk.first_above_land_y(structure_x, structure_z);
-        let global_center_pos = GlobalBlockPos {
-            x: structure_x,
-            y: structure_y,
-            z: structure_z,
+        let mut chunk = MetaChunk {
+            pos,
+            chunks,
+            seed: chunk_generator.seed,
+            heightmaps: ColumnHeightmaps::default(),
+            modified: false,
         };
-        place_square(&global_center_pos, 10, &mut chunk);
-
-        let mut rng = rand::thread_rng();
-        let location_range = Uniform::from(5..(METACHUNKSIZE * CHUNKSIZE) - 5);
-        let normal_distribution = Normal::new(0f32, 50f32).unwrap();
-        let x_offset = location_range.sample(&mut rng) as i32;
-        let z_offset = location_range.sample(&mut rng) as i32;
-        for _ in 0..300 {
-            let x_diff: i32 = normal_distribution.sample(&mut rng) as i32;
-            let z_diff: i32 = normal_distribution.sample(&mut rng) as i32;
-
-            let structure_x = pos.x * METACHUNKSIZE as i32 * CHUNKSIZE as i32 + x_offset + x_diff;
-            let structure_z = pos.z * METACHUNKSIZE as i32 * CHUNKSIZE as i32 + z_offset + z_diff;
-            let structure_y = chunk.first_above_land_y(structure_x, structure_z);
-            let tree_pos = GlobalBlockPos {
-                x: structure_x,
-                y: structure_y,
-                z: structure_z,
-            };
-            match chunk.get_block(&tree_pos.get_diff(0, -1, 0)) {
-                255 => {}
-                b => {
-                    if get_blocktype(b) == BlockType::Grass {
-                        place_tree(&tree_pos, &mut chunk);
-                    }
-                }
-            }
-        }
-
-        let structure_x = pos.x * METACHUNKSIZE as i32 * CHUNKSIZE as i32 + pos.x;
-        let structure_z = pos.z * METACHUNKSIZE as i32 * CHUNKSIZE as i32 + pos.z;
-        for y in chunk.first_above_land_y(structure_x, structure_z)
-            ..chunk.first_above_land_y(structure_x, structure_z) + 10
-        {
-            let global_center_pos = GlobalBlockPos {
-                x: structure_x,
-                y,
-                z: structure_z,
-            };
-            chunk.set_block(&global_center_pos, get_blockid(BlockType::Sand));
-        }
+        let heightmaps = ColumnHeightmaps::build(|x, y, z| chunk.get_local_block(x, y, z));
+        chunk.heightmaps = heightmaps;
+        chunk_generator.decorate(&mut chunk);
+        // decorations can be regenerated, so they don't count as modifications
+        chunk.modified = false;
 
         return chunk;
     }
+    pub fn save_path(save_dir: &str, pos: &MetaChunkPos) -> String {
+        format!("{}/{}_{}.metachunk", save_dir, pos.x, pos.z)
+    }
+    pub fn load(save_dir: &str, pos: MetaChunkPos, seed: u32) -> Result<MetaChunk, VoxIoError> {
+        let filename = MetaChunk::save_path(save_dir, &pos);
+        let chunk = MetaChunk::load_file(&filename)?;
+        if chunk.pos != pos || chunk.seed != seed {
+            return Err(VoxIoError::Corrupt(
+                filename,
+                format!("it contains metachunk {:?} with seed {}", chunk.pos, chunk.seed),
+            ));
+        }
+        return Ok(chunk);
+    }
+    pub fn load_file(filename: &str) -> Result<MetaChunk, VoxIoError> {
+        return read_meta_chunk_from_file(filename);
+    }
+    pub fn save(&self, save_dir: &str) -> Result<CompressionStats, VoxIoError> {
+        return MetaChunk::save_serialized(save_dir, &self.pos, &self.serialize(save_dir)?);
+    }
+    pub fn serialize(&self, save_dir: &str) -> Result<Vec<u8>, VoxIoError> {
+        return serialize_for_file(&MetaChunk::save_path(save_dir, &self.pos), self);
+    }
+    pub fn deserialize(save_dir: &str, pos: &MetaChunkPos, raw: &[u8]) -> Result<MetaChunk, VoxIoError> {
+        return deserialize_serialized(&MetaChunk::save_path(save_dir, pos), raw);
+    }
+    // writes the output of serialize, which can be done on any thread
+    pub fn save_serialized(save_dir: &str, pos: &MetaChunkPos, raw: &[u8]) -> Result<CompressionStats, VoxIoError> {
+        std::fs::create_dir_all(save_dir).map_err(|e| VoxIoError::Io(save_dir.to_string(), e))?;
+        return write_serialized_to_file(&MetaChunk::save_path(save_dir, pos), raw, SAVE_CODEC);
+    }
     pub fn first_above_land_y(&self, x: i32, z: i32) -> i32 {
-        let mut y = METACHUNKSIZE as i32 * CHUNKSIZE as i32 - 1;
-        while let b = self.get_block(&GlobalBlockPos { x, y, z }) {
-            if b == 255{
-                return y+1;
-            }
-            let b_type = get_blocktype(b);
-            if b_type == BlockType::Grass
-                || b_type == BlockType::Water
-                || b_type == BlockType::Dirt
-                || b_type == BlockType::Stone
-            {
-                return y + 1;
-            }
-            y -= 1;
+        return self.get_height(HeightmapKind::MotionBlocking, x, z) + 1;
+    }
+    // y of the highest block counted by the heightmap kind in the column at global x and z
+    pub fn get_height(&self, kind: HeightmapKind, x: i32, z: i32) -> i32 {
+        let size = METACHUNKSIZE as i32 * CHUNKSIZE as i32;
+        let (local_x, local_z) = self.to_local_column(x, z);
+        if local_x < 0 || local_x >= size || local_z < 0 || local_z >= size {
+            return -1;
         }
-        return y;
+        return self.heightmaps.get(kind, local_x, local_z);
+    }
+    fn to_local_column(&self, x: i32, z: i32) -> (i32, i32) {
+        let size = METACHUNKSIZE as i32 * CHUNKSIZE as i32;
+        (x - self.pos.x * size, z - self.pos.z * size)
+    }
+    fn get_local_block(&self, x: i32, y: i32, z: i32) -> BlockId {
+        let size = METACHUNKSIZE as i32 * CHUNKSIZE as i32;
+        self.get_block(&GlobalBlockPos {
+            x: x + self.pos.x * size,
+            y,
+            z: z + self.pos.z * size,
+        })
     }
 
     pub fn set_block(&mut self, pos: &GlobalBlockPos, block: BlockId) {
+        if !self.contains_block(pos) {
+            return;
+        }
         let chunk_pos = pos.get_local_chunk();
         let chunk = self.get_chunk_mut(&chunk_pos);
         match chunk {
             Some(c) => c.set_block(block, &pos.get_local_pos()),
-            None => {}
+            None => return,
         }
+        self.modified = true;
+        let (x, z) = self.to_local_column(pos.x, pos.z);
+        let mut heightmaps = std::mem::take(&mut self.heightmaps);
+        heightmaps.on_block_set(x, pos.y, z, block, |x, y, z| self.get_local_block(x, y, z));
+        self.heightmaps = heightmaps;
+    }
+
+    pub fn contains_block(&self, pos: &GlobalBlockPos) -> bool {
+        let size = METACHUNKSIZE as i32 * CHUNKSIZE as i32;
+        let (x, z) = self.to_local_column(pos.x, pos.z);
+        x >= 0 && x < size && z >= 0 && z < size && pos.y >= 0 && pos.y < size
     }
 
     pub fn get_block(&self, pos: &GlobalBlockPos) -> BlockId {
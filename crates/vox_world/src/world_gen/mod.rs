@@ -1,5 +1,14 @@
 pub mod basic;
 pub mod chunk;
-pub mod chunk_gen_thread;
+pub mod chunk_gen_pool;
+pub mod column_heightmaps;
+pub mod generation_stage;
+pub mod heightmap_stage;
 pub mod meta_chunk;
+pub mod ores;
+pub mod preset_stages;
+pub mod preview;
+pub mod seeded_rng;
+pub mod structure_stages;
 pub mod vertex_generation;
+pub mod world_preset;
@@ -0,0 +1,126 @@
+use crate::blocks::block::{get_blockid, BlockId};
+use crate::blocks::block_type::BlockType;
+use crate::world_gen::basic::ChunkGenerator;
+use crate::world_gen::generation_stage::GenerationStage;
+use crate::world_gen::seeded_rng::seeded_rng;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use vox_core::constants::CHUNKSIZE;
+use vox_core::positions::ChunkPos;
+use vox_core::utils::coord_to_array_indice;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OreConfig {
+    pub block: BlockType,
+    pub host: BlockType,
+    pub min_y: i32,
+    pub max_y: i32,
+    pub vein_size: u32,
+    // average amount of veins per chunk
+    pub frequency: f32,
+}
+
+// 64 veins of 256 blocks replace the host of a whole chunk several times over, larger values only
+// slow generation down
+pub const MAX_VEINS_PER_CHUNK: f32 = 64.0;
+pub const MAX_VEIN_SIZE: u32 = 256;
+
+impl OreConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(self.frequency >= 0.0 && self.frequency <= MAX_VEINS_PER_CHUNK) {
+            return Err(format!(
+                "{:?} needs a frequency between 0 and {}, got {}",
+                self.block, MAX_VEINS_PER_CHUNK, self.frequency
+            ));
+        }
+        if self.vein_size == 0 || self.vein_size > MAX_VEIN_SIZE {
+            return Err(format!(
+                "{:?} needs a vein_size between 1 and {}, got {}",
+                self.block, MAX_VEIN_SIZE, self.vein_size
+            ));
+        }
+        if self.min_y > self.max_y {
+            return Err(format!(
+                "{:?} needs min_y <= max_y, got {} and {}",
+                self.block, self.min_y, self.max_y
+            ));
+        }
+        return Ok(());
+    }
+}
+
+pub fn default_ores() -> Vec<OreConfig> {
+    vec![
+        OreConfig {
+            block: BlockType::CoalOre,
+            host: BlockType::Stone,
+            min_y: 0,
+            max_y: 48,
+            vein_size: 12,
+            frequency: 4.0,
+        },
+        OreConfig {
+            block: BlockType::IronOre,
+            host: BlockType::Stone,
+            min_y: 0,
+            max_y: 32,
+            vein_size: 8,
+            frequency: 2.0,
+        },
+        OreConfig {
+            block: BlockType::GoldOre,
+            host: BlockType::Stone,
+            min_y: 0,
+            max_y: 16,
+            vein_size: 6,
+            frequency: 0.5,
+        },
+        OreConfig {
+            block: BlockType::DiamondOre,
+            host: BlockType::Stone,
+            min_y: 0,
+            max_y: 8,
+            vein_size: 4,
+            frequency: 0.25,
+        },
+    ]
+}
+
+pub struct OreStage {
+    pub ores: Vec<OreConfig>,
+}
+
+impl GenerationStage for OreStage {
+    fn generate_chunk(&self, chunk_generator: &ChunkGenerator, pos: &ChunkPos, chunk: &mut Vec<BlockId>) {
+        for (i, ore) in self.ores.iter().enumerate() {
+            let chunk_min_y = pos.y * CHUNKSIZE as i32;
+            if ore.max_y < chunk_min_y || ore.min_y >= chunk_min_y + CHUNKSIZE as i32 {
+                continue;
+            }
+            let mut rng = seeded_rng(chunk_generator.seed, pos.x, pos.y, pos.z, i as u64);
+            let mut veins = ore.frequency.floor() as u32;
+            if rng.gen::<f32>() < ore.frequency.fract() {
+                veins += 1;
+            }
+            for _ in 0..veins {
+                let mut x = rng.gen_range(0..CHUNKSIZE as i32);
+                let mut y = rng.gen_range(0..CHUNKSIZE as i32);
+                let mut z = rng.gen_range(0..CHUNKSIZE as i32);
+                if y + chunk_min_y < ore.min_y || y + chunk_min_y > ore.max_y {
+                    continue;
+                }
+                for _ in 0..ore.vein_size {
+                    let index = coord_to_array_indice(x as u32, y as u32, z as u32, CHUNKSIZE as u32);
+                    if chunk[index] == get_blockid(ore.host) {
+                        chunk[index] = get_blockid(ore.block);
+                    }
+                    match rng.gen_range(0..3) {
+                        0 => x = (x + rng.gen_range(-1..=1)).clamp(0, CHUNKSIZE as i32 - 1),
+                        1 => y = (y + rng.gen_range(-1..=1)).clamp(0, CHUNKSIZE as i32 - 1),
+                        _ => z = (z + rng.gen_range(-1..=1)).clamp(0, CHUNKSIZE as i32 - 1),
+                    }
+                }
+            }
+        }
+    }
+}
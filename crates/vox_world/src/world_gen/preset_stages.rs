@@ -0,0 +1,106 @@
+use crate::blocks::block::{get_blockid, placeable_blocks, BlockId};
+use crate::blocks::block_type::BlockType;
+use crate::world_gen::basic::ChunkGenerator;
+use crate::world_gen::generation_stage::GenerationStage;
+use serde::{Deserialize, Serialize};
+use vox_core::constants::CHUNKSIZE;
+use vox_core::positions::ChunkPos;
+use vox_core::utils::{coord_to_array_indice, wrap};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FlatLayer {
+    pub block: BlockType,
+    pub thickness: u32,
+}
+
+// stacks the layers on top of each other starting at y = 0
+pub struct FlatLayersStage {
+    pub layers: Vec<FlatLayer>,
+}
+
+impl GenerationStage for FlatLayersStage {
+    fn generate_chunk(&self, _: &ChunkGenerator, pos: &ChunkPos, chunk: &mut Vec<BlockId>) {
+        let mut layer_bottom = 0;
+        for layer in &self.layers {
+            let layer_top = layer_bottom + layer.thickness as i32;
+            for y in 0..CHUNKSIZE as i32 {
+                let global_y = y + pos.y * CHUNKSIZE as i32;
+                if global_y < layer_bottom || global_y >= layer_top {
+                    continue;
+                }
+                for x in 0..CHUNKSIZE as u32 {
+                    for z in 0..CHUNKSIZE as u32 {
+                        chunk[coord_to_array_indice(x, y as u32, z, CHUNKSIZE as u32)] = get_blockid(layer.block);
+                    }
+                }
+            }
+            layer_bottom = layer_top;
+        }
+    }
+}
+
+// square platform centered on the world origin, so a player has something to stand on in an empty world
+pub struct SpawnPlatformStage {
+    pub block: BlockType,
+    pub radius: i32,
+    pub y: i32,
+}
+
+impl GenerationStage for SpawnPlatformStage {
+    fn generate_chunk(&self, _: &ChunkGenerator, pos: &ChunkPos, chunk: &mut Vec<BlockId>) {
+        let y = self.y - pos.y * CHUNKSIZE as i32;
+        if y < 0 || y >= CHUNKSIZE as i32 {
+            return;
+        }
+        for x in 0..CHUNKSIZE as i32 {
+            for z in 0..CHUNKSIZE as i32 {
+                let global_x = x + pos.x * CHUNKSIZE as i32;
+                let global_z = z + pos.z * CHUNKSIZE as i32;
+                if global_x.abs() <= self.radius && global_z.abs() <= self.radius {
+                    chunk[coord_to_array_indice(x as u32, y as u32, z as u32, CHUNKSIZE as u32)] = get_blockid(self.block);
+                }
+            }
+        }
+    }
+}
+
+// lays out every known block kind on a grid in the positive x/z quadrant
+pub struct DebugGridStage {
+    pub spacing: i32,
+    pub y: i32,
+}
+
+impl GenerationStage for DebugGridStage {
+    fn generate_chunk(&self, _: &ChunkGenerator, pos: &ChunkPos, chunk: &mut Vec<BlockId>) {
+        let y = self.y - pos.y * CHUNKSIZE as i32;
+        if y < 0 || y >= CHUNKSIZE as i32 {
+            return;
+        }
+        let kinds = placeable_blocks();
+        let columns = (kinds.len() as f32).sqrt().ceil() as i32;
+        for x in 0..CHUNKSIZE as i32 {
+            for z in 0..CHUNKSIZE as i32 {
+                let global_x = x + pos.x * CHUNKSIZE as i32;
+                let global_z = z + pos.z * CHUNKSIZE as i32;
+                if global_x < 0
+                    || global_z < 0
+                    || wrap(global_x, self.spacing) != 0
+                    || wrap(global_z, self.spacing) != 0
+                {
+                    continue;
+                }
+                let column = global_x / self.spacing;
+                let row = global_z / self.spacing;
+                if column >= columns {
+                    continue;
+                }
+                match kinds.get((row * columns + column) as usize) {
+                    Some(block) => {
+                        chunk[coord_to_array_indice(x as u32, y as u32, z as u32, CHUNKSIZE as u32)] = *block;
+                    }
+                    None => {}
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,212 @@
+use crate::blocks::block::{get_blockid, get_blocktype, BlockId};
+use crate::blocks::block_type::BlockType;
+use crate::world_gen::basic::ChunkGenerator;
+use rayon::prelude::*;
+use vox_core::constants::{CHUNKSIZE, COLORS, METACHUNKSIZE};
+use vox_core::positions::ChunkPos;
+use vox_core::utils::coord_to_array_indice;
+use vox_io::io::error::VoxIoError;
+use vox_io::io::image_writer::write_png_to_file;
+
+#[derive(Debug, Clone, Copy)]
+pub struct ColumnSample {
+    // highest block that is neither air nor water, -1 for an empty column
+    pub ground_height: i32,
+    // highest block that is not air
+    pub surface: BlockId,
+    pub water_depth: i32,
+}
+
+// the generator has no biomes of its own yet, so columns are classified by their terrain
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PreviewBiome {
+    Void,
+    Ocean,
+    Beach,
+    Plains,
+    Hills,
+    Mountains,
+}
+
+// top down view of the terrain stages of a generator, decorations are not included
+pub struct WorldPreview {
+    pub min_x: i32,
+    pub min_z: i32,
+    pub width: u32,
+    pub depth: u32,
+    pub water_level: i32,
+    pub columns: Vec<ColumnSample>,
+}
+
+impl WorldPreview {
+    pub fn generate(generator: &ChunkGenerator, min_x: i32, min_z: i32, width: u32, depth: u32) -> WorldPreview {
+        let chunk_size = CHUNKSIZE as i32;
+        let mut chunk_columns = Vec::new();
+        for chunk_x in min_x.div_euclid(chunk_size)..=(min_x + width as i32 - 1).div_euclid(chunk_size) {
+            for chunk_z in min_z.div_euclid(chunk_size)..=(min_z + depth as i32 - 1).div_euclid(chunk_size) {
+                chunk_columns.push((chunk_x, chunk_z));
+            }
+        }
+        let samples: Vec<(i32, i32, Vec<ColumnSample>)> = chunk_columns
+            .par_iter()
+            .map(|(chunk_x, chunk_z)| (*chunk_x, *chunk_z, sample_chunk_column(generator, *chunk_x, *chunk_z)))
+            .collect();
+
+        let mut columns = vec![
+            ColumnSample {
+                ground_height: -1,
+                surface: get_blockid(BlockType::Air),
+                water_depth: 0,
+            };
+            (width * depth) as usize
+        ];
+        for (chunk_x, chunk_z, chunk_samples) in samples {
+            for x in 0..chunk_size {
+                for z in 0..chunk_size {
+                    let preview_x = x + chunk_x * chunk_size - min_x;
+                    let preview_z = z + chunk_z * chunk_size - min_z;
+                    if preview_x < 0 || preview_z < 0 || preview_x >= width as i32 || preview_z >= depth as i32 {
+                        continue;
+                    }
+                    columns[(preview_x + preview_z * width as i32) as usize] = chunk_samples[(x + z * chunk_size) as usize];
+                }
+            }
+        }
+        return WorldPreview {
+            min_x,
+            min_z,
+            width,
+            depth,
+            water_level: generator.water_level,
+            columns,
+        };
+    }
+    pub fn get_biome(&self, column: &ColumnSample) -> PreviewBiome {
+        let world_height = (CHUNKSIZE * METACHUNKSIZE) as i32;
+        if column.ground_height < 0 {
+            return PreviewBiome::Void;
+        }
+        if column.water_depth > 0 {
+            return PreviewBiome::Ocean;
+        }
+        if get_blocktype(column.surface) == BlockType::Sand {
+            return PreviewBiome::Beach;
+        }
+        let above_water = column.ground_height - self.water_level;
+        let range = world_height - self.water_level;
+        return if above_water > range / 2 {
+            PreviewBiome::Mountains
+        } else if above_water > range / 4 {
+            PreviewBiome::Hills
+        } else {
+            PreviewBiome::Plains
+        };
+    }
+    pub fn heightmap_pixels(&self) -> Vec<[u8; 4]> {
+        let world_height = (CHUNKSIZE * METACHUNKSIZE) as i32;
+        self.columns
+            .iter()
+            .map(|c| {
+                let v = ((c.ground_height + 1) * 255 / world_height) as u8;
+                [v, v, v, 255]
+            })
+            .collect()
+    }
+    pub fn surface_pixels(&self) -> Vec<[u8; 4]> {
+        self.columns
+            .iter()
+            .map(|c| {
+                if get_blocktype(c.surface) == BlockType::Air {
+                    return [0, 0, 0, 255];
+                }
+                let color = COLORS[c.surface as usize];
+                [color[0] as u8, color[1] as u8, color[2] as u8, 255]
+            })
+            .collect()
+    }
+    pub fn biome_pixels(&self) -> Vec<[u8; 4]> {
+        self.columns
+            .iter()
+            .map(|c| match self.get_biome(c) {
+                PreviewBiome::Void => [0, 0, 0, 255],
+                PreviewBiome::Ocean => [30, 60, 200, 255],
+                PreviewBiome::Beach => [230, 210, 140, 255],
+                PreviewBiome::Plains => [90, 190, 70, 255],
+                PreviewBiome::Hills => [40, 120, 40, 255],
+                PreviewBiome::Mountains => [150, 150, 150, 255],
+            })
+            .collect()
+    }
+    // deeper water is darker, land is black
+    pub fn water_pixels(&self) -> Vec<[u8; 4]> {
+        self.columns
+            .iter()
+            .map(|c| {
+                if c.water_depth == 0 {
+                    return [0, 0, 0, 255];
+                }
+                let v = (255 - (c.water_depth * 8).min(200)) as u8;
+                [0, v / 2, v, 255]
+            })
+            .collect()
+    }
+    pub fn write_pngs(&self, directory: &str) -> Result<(), VoxIoError> {
+        std::fs::create_dir_all(directory).map_err(|e| VoxIoError::Io(directory.to_string(), e))?;
+        let maps = [
+            ("heightmap", self.heightmap_pixels()),
+            ("surface", self.surface_pixels()),
+            ("biomes", self.biome_pixels()),
+            ("water", self.water_pixels()),
+        ];
+        for (name, pixels) in maps.iter() {
+            let filename = format!("{}/{}.png", directory, name);
+            write_png_to_file(&filename, self.width, self.depth, pixels)?;
+            println!("wrote {}", filename);
+        }
+        return Ok(());
+    }
+}
+
+fn sample_chunk_column(generator: &ChunkGenerator, chunk_x: i32, chunk_z: i32) -> Vec<ColumnSample> {
+    let chunks: Vec<Vec<BlockId>> = (0..METACHUNKSIZE as i32)
+        .map(|chunk_y| {
+            generator.full_generation_pass(&ChunkPos {
+                x: chunk_x,
+                y: chunk_y,
+                z: chunk_z,
+            })
+        })
+        .collect();
+    let mut samples = Vec::with_capacity(CHUNKSIZE * CHUNKSIZE);
+    for z in 0..CHUNKSIZE as u32 {
+        for x in 0..CHUNKSIZE as u32 {
+            let mut sample = ColumnSample {
+                ground_height: -1,
+                surface: get_blockid(BlockType::Air),
+                water_depth: 0,
+            };
+            for y in (0..(CHUNKSIZE * METACHUNKSIZE) as u32).rev() {
+                let chunk = &chunks[y as usize / CHUNKSIZE];
+                let block = chunk[coord_to_array_indice(x, y % CHUNKSIZE as u32, z, CHUNKSIZE as u32)];
+                match get_blocktype(block) {
+                    BlockType::Air => continue,
+                    BlockType::Water => {
+                        if get_blocktype(sample.surface) == BlockType::Air {
+                            sample.surface = block;
+                        }
+                        sample.water_depth += 1;
+                    }
+                    _ => {
+                        if get_blocktype(sample.surface) == BlockType::Air {
+                            sample.surface = block;
+                        }
+                        sample.ground_height = y as i32;
+                        break;
+                    }
+                }
+            }
+            samples.push(sample);
+        }
+    }
+    return samples;
+}
@@ -0,0 +1,18 @@
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+// rng that always produces the same sequence for the same seed, position and salt
+pub fn seeded_rng(seed: u32, x: i32, y: i32, z: i32, salt: u64) -> StdRng {
+    let mut hash = splitmix64(seed as u64 ^ salt.wrapping_mul(0x9E37_79B9_7F4A_7C15));
+    hash = splitmix64(hash ^ x as u32 as u64);
+    hash = splitmix64(hash ^ y as u32 as u64);
+    hash = splitmix64(hash ^ z as u32 as u64);
+    StdRng::seed_from_u64(hash)
+}
+
+fn splitmix64(val: u64) -> u64 {
+    let mut z = val.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
@@ -0,0 +1,227 @@
+use crate::algorithms::bfs_world::bfs_world_air;
+use crate::blocks::block::{get_blockid, get_blocktype};
+use crate::blocks::block_type::BlockType;
+use crate::structures::square::place_square;
+use crate::structures::template::{Placement, Rotation, StructureTemplate};
+use crate::structures::tree::{place_tree, TreeSpecies};
+use crate::world_gen::basic::ChunkGenerator;
+use crate::world_gen::generation_stage::GenerationStage;
+use crate::world_gen::meta_chunk::MetaChunk;
+use crate::world_gen::seeded_rng::seeded_rng;
+use rand::distributions::{Distribution, Uniform};
+use rand::rngs::StdRng;
+use rand::Rng;
+use rand_distr::Normal;
+use serde::{Deserialize, Serialize};
+use vox_core::constants::{CHUNKSIZE, METACHUNKSIZE};
+use vox_core::positions::GlobalBlockPos;
+
+pub struct SandBlobStage {
+    pub offset_x: i32,
+    pub offset_z: i32,
+    pub depth: u32,
+}
+
+impl GenerationStage for SandBlobStage {
+    fn decorate(&self, _: &ChunkGenerator, chunk: &mut MetaChunk) {
+        let structure_x = chunk.pos.x * METACHUNKSIZE as i32 * CHUNKSIZE as i32 + self.offset_x;
+        let structure_z = chunk.pos.z * METACHUNKSIZE as i32 * CHUNKSIZE as i32 + self.offset_z;
+        let structure_y = chunk.first_above_land_y(structure_x, structure_z);
+        let global_center_pos = GlobalBlockPos {
+            x: structure_x,
+            y: structure_y,
+            z: structure_z,
+        };
+        bfs_world_air(
+            &global_center_pos,
+            self.depth,
+            chunk,
+            get_blockid(BlockType::Sand),
+        );
+    }
+}
+
+pub struct SquareStage {
+    pub offset_x: i32,
+    pub offset_z: i32,
+    pub size: u32,
+}
+
+impl GenerationStage for SquareStage {
+    fn decorate(&self, _: &ChunkGenerator, chunk: &mut MetaChunk) {
+        let structure_x = chunk.pos.x * METACHUNKSIZE as i32 * CHUNKSIZE as i32 + self.offset_x;
+        let structure_z = chunk.pos.z * METACHUNKSIZE as i32 * CHUNKSIZE as i32 + self.offset_z;
+        let structure_y = chunk.first_above_land_y(structure_x, structure_z);
+        let global_center_pos = GlobalBlockPos {
+            x: structure_x,
+            y: structure_y,
+            z: structure_z,
+        };
+        place_square(&global_center_pos, self.size, chunk);
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeightedSpecies {
+    pub species: TreeSpecies,
+    pub weight: f32,
+}
+
+impl WeightedSpecies {
+    // pick_species draws from the sum of the weights, so every weight has to be a finite number of at least 0
+    pub fn validate(&self) -> Result<(), String> {
+        if !(self.weight.is_finite() && self.weight >= 0.0) {
+            return Err(format!(
+                "{} needs a finite weight of at least 0, got {}",
+                self.species.name, self.weight
+            ));
+        }
+        return self.species.validate();
+    }
+}
+
+pub fn default_tree_species() -> Vec<WeightedSpecies> {
+    vec![
+        WeightedSpecies {
+            species: TreeSpecies::oak(),
+            weight: 6.0,
+        },
+        WeightedSpecies {
+            species: TreeSpecies::birch(),
+            weight: 3.0,
+        },
+        WeightedSpecies {
+            species: TreeSpecies::pine(),
+            weight: 1.0,
+        },
+        WeightedSpecies {
+            species: TreeSpecies::dead_bush(),
+            weight: 1.0,
+        },
+    ]
+}
+
+const TREE_SALT: u64 = 0x7472_6565;
+
+// scatters trees around a random point in the metachunk, every tree picks a species
+// that can grow on the block it lands on
+pub struct TreeStage {
+    pub count: u32,
+    pub spread: f32,
+    pub species: Vec<WeightedSpecies>,
+}
+
+impl TreeStage {
+    fn pick_species(&self, ground: BlockType, rng: &mut StdRng) -> Option<&TreeSpecies> {
+        let candidates: Vec<&WeightedSpecies> = self.species.iter().filter(|s| s.species.ground == ground).collect();
+        let total: f32 = candidates.iter().map(|s| s.weight).sum();
+        if total <= 0.0 {
+            return None;
+        }
+        let mut choice = rng.gen_range(0.0..total);
+        for candidate in candidates {
+            if choice < candidate.weight {
+                return Some(&candidate.species);
+            }
+            choice -= candidate.weight;
+        }
+        return None;
+    }
+}
+
+impl GenerationStage for TreeStage {
+    fn decorate(&self, chunk_generator: &ChunkGenerator, chunk: &mut MetaChunk) {
+        let pos = chunk.pos;
+        let mut rng = seeded_rng(chunk_generator.seed, pos.x, 0, pos.z, TREE_SALT);
+        let location_range = Uniform::from(5..(METACHUNKSIZE * CHUNKSIZE) - 5);
+        let normal_distribution = Normal::new(0f32, self.spread).unwrap();
+        let x_offset = location_range.sample(&mut rng) as i32;
+        let z_offset = location_range.sample(&mut rng) as i32;
+        for _ in 0..self.count {
+            let x_diff: i32 = normal_distribution.sample(&mut rng) as i32;
+            let z_diff: i32 = normal_distribution.sample(&mut rng) as i32;
+
+            let structure_x = pos.x * METACHUNKSIZE as i32 * CHUNKSIZE as i32 + x_offset + x_diff;
+            let structure_z = pos.z * METACHUNKSIZE as i32 * CHUNKSIZE as i32 + z_offset + z_diff;
+            let tree_pos = GlobalBlockPos {
+                x: structure_x,
+                y: 0,
+                z: structure_z,
+            };
+            if !chunk.contains_block(&tree_pos) {
+                continue;
+            }
+            let tree_pos = GlobalBlockPos {
+                y: chunk.first_above_land_y(structure_x, structure_z),
+                ..tree_pos
+            };
+            let ground = tree_pos.get_diff(0, -1, 0);
+            if !chunk.contains_block(&ground) {
+                continue;
+            }
+            let ground_type = get_blocktype(chunk.get_block(&ground));
+            if let Some(species) = self.pick_species(ground_type, &mut rng) {
+                place_tree(&tree_pos, chunk, species, &mut rng);
+            }
+        }
+    }
+}
+
+const STRUCTURE_SALT: u64 = 0x7374_7275;
+
+// places a template on the surface at the same offset in every metachunk
+pub struct StructureStage {
+    pub template: Option<StructureTemplate>,
+    pub offset_x: i32,
+    pub offset_z: i32,
+    pub placement: Placement,
+    pub random_rotation: bool,
+}
+
+impl GenerationStage for StructureStage {
+    fn decorate(&self, chunk_generator: &ChunkGenerator, chunk: &mut MetaChunk) {
+        let template = match &self.template {
+            Some(t) => t,
+            None => return,
+        };
+        let structure_x = chunk.pos.x * METACHUNKSIZE as i32 * CHUNKSIZE as i32 + self.offset_x;
+        let structure_z = chunk.pos.z * METACHUNKSIZE as i32 * CHUNKSIZE as i32 + self.offset_z;
+        let structure_y = chunk.first_above_land_y(structure_x, structure_z);
+        let mut placement = self.placement;
+        if self.random_rotation {
+            let mut rng = seeded_rng(chunk_generator.seed, chunk.pos.x, 0, chunk.pos.z, STRUCTURE_SALT);
+            placement.rotation = Rotation::from_index(rng.gen_range(0..4));
+        }
+        template.place(
+            &GlobalBlockPos {
+                x: structure_x,
+                y: structure_y,
+                z: structure_z,
+            },
+            &placement,
+            chunk,
+        );
+    }
+}
+
+pub struct DebugPillarStage {
+    pub height: i32,
+}
+
+impl GenerationStage for DebugPillarStage {
+    fn decorate(&self, _: &ChunkGenerator, chunk: &mut MetaChunk) {
+        let pos = chunk.pos;
+        let structure_x = pos.x * METACHUNKSIZE as i32 * CHUNKSIZE as i32 + pos.x;
+        let structure_z = pos.z * METACHUNKSIZE as i32 * CHUNKSIZE as i32 + pos.z;
+        for y in chunk.first_above_land_y(structure_x, structure_z)
+            ..chunk.first_above_land_y(structure_x, structure_z) + self.height
+        {
+            let global_center_pos = GlobalBlockPos {
+                x: structure_x,
+                y,
+                z: structure_z,
+            };
+            chunk.set_block(&global_center_pos, get_blockid(BlockType::Sand));
+        }
+    }
+}
@@ -0,0 +1,366 @@
+use crate::world_gen::basic::{CaveStage, GrassStage, LandmassStage, WaterStage};
+use crate::world_gen::generation_stage::GenerationStage;
+use crate::world_gen::heightmap_stage::{HeightmapConfig, HeightmapStage};
+use crate::blocks::block_type::BlockType;
+use crate::structures::template::{Placement, StructureTemplate};
+use crate::world_gen::ores::{default_ores, OreConfig, OreStage};
+use crate::world_gen::preset_stages::{DebugGridStage, FlatLayer, FlatLayersStage, SpawnPlatformStage};
+use crate::world_gen::structure_stages::{
+    default_tree_species, DebugPillarStage, SandBlobStage, SquareStage, StructureStage, TreeStage,
+    WeightedSpecies,
+};
+use serde::{Deserialize, Serialize};
+use vox_core::constants::{CHUNKSIZE, METACHUNKSIZE};
+use vox_io::io::config_reader::read_config_from_file;
+use vox_io::io::error::VoxIoError;
+
+pub const DEFAULT_PRESET_FILE: &str = "presets/default.ron";
+pub const PRESET_DIR: &str = "presets";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorldPreset {
+    pub name: String,
+    pub terrain: TerrainConfig,
+    pub stages: Vec<StageEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TerrainConfig {
+    pub octaves: usize,
+    pub persistence: f64,
+    pub water_level: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StageEntry {
+    pub name: String,
+    pub stage: StageConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum StageConfig {
+    Landmass,
+    Caves {
+        cheese_scale: f64,
+        cheese_threshold: f64,
+        worm_scale: f64,
+        worm_width: f64,
+        seal_depth: i32,
+    },
+    Ores(Vec<OreConfig>),
+    Water,
+    Grass { max_height: i32 },
+    SandBlob { offset_x: i32, offset_z: i32, depth: u32 },
+    Square { offset_x: i32, offset_z: i32, size: u32 },
+    Trees {
+        count: u32,
+        spread: f32,
+        #[serde(default = "default_tree_species")]
+        species: Vec<WeightedSpecies>,
+    },
+    DebugPillar { height: i32 },
+    FlatLayers(Vec<FlatLayer>),
+    SpawnPlatform { block: BlockType, radius: i32, y: i32 },
+    DebugGrid { spacing: i32, y: i32 },
+    Heightmap(HeightmapConfig),
+    // template is a file name in the structure directory, without extension
+    Structure {
+        template: String,
+        offset_x: i32,
+        offset_z: i32,
+        placement: Placement,
+        random_rotation: bool,
+    },
+}
+
+impl WorldPreset {
+    pub fn load(filename: &str) -> Result<WorldPreset, VoxIoError> {
+        let preset: WorldPreset = read_config_from_file(filename)?;
+        preset
+            .validate()
+            .map_err(|reason| VoxIoError::Corrupt(filename.to_string(), reason))?;
+        return Ok(preset);
+    }
+    // catches settings that would make a stage panic while generating, before any worker runs it
+    pub fn validate(&self) -> Result<(), String> {
+        for entry in &self.stages {
+            entry
+                .stage
+                .validate()
+                .map_err(|reason| format!("stage {}: {}", entry.name, reason))?;
+        }
+        return Ok(());
+    }
+    pub fn load_or_default(filename: &str) -> WorldPreset {
+        match WorldPreset::load(filename) {
+            Ok(preset) => preset,
+            Err(VoxIoError::NotFound(_)) => {
+                println!("world preset {} does not exist, using the default preset", filename);
+                WorldPreset::default()
+            }
+            Err(e) => {
+                println!("could not load world preset: {}, using the default preset", e);
+                WorldPreset::default()
+            }
+        }
+    }
+    // built in presets are looked up by name first, anything else is loaded from the preset directory
+    pub fn from_name(name: &str) -> WorldPreset {
+        match name {
+            "superflat" => WorldPreset::superflat(vec![
+                FlatLayer {
+                    block: BlockType::Stone,
+                    thickness: 10,
+                },
+                FlatLayer {
+                    block: BlockType::Dirt,
+                    thickness: 3,
+                },
+                FlatLayer {
+                    block: BlockType::Grass,
+                    thickness: 1,
+                },
+            ]),
+            "void" => WorldPreset::void(),
+            "debug" => WorldPreset::debug_grid(),
+            _ if name.ends_with(".png") => WorldPreset::heightmap(HeightmapConfig::new(name, None)),
+            _ => WorldPreset::load_or_default(&format!("{}/{}.ron", PRESET_DIR, name)),
+        }
+    }
+    pub fn superflat(layers: Vec<FlatLayer>) -> WorldPreset {
+        WorldPreset {
+            name: "superflat".to_string(),
+            terrain: TerrainConfig::default(),
+            stages: vec![StageEntry::new("layers", StageConfig::FlatLayers(layers))],
+        }
+    }
+    pub fn void() -> WorldPreset {
+        WorldPreset {
+            name: "void".to_string(),
+            terrain: TerrainConfig::default(),
+            stages: vec![StageEntry::new(
+                "spawn platform",
+                StageConfig::SpawnPlatform {
+                    block: BlockType::Stone,
+                    radius: 2,
+                    y: 31,
+                },
+            )],
+        }
+    }
+    pub fn debug_grid() -> WorldPreset {
+        WorldPreset {
+            name: "debug".to_string(),
+            terrain: TerrainConfig::default(),
+            stages: vec![
+                StageEntry::new(
+                    "floor",
+                    StageConfig::FlatLayers(vec![FlatLayer {
+                        block: BlockType::Stone,
+                        thickness: 1,
+                    }]),
+                ),
+                StageEntry::new("grid", StageConfig::DebugGrid { spacing: 2, y: 2 }),
+            ],
+        }
+    }
+    // the heightmap stage places its own water and surface, so the noise based stages are left out
+    pub fn heightmap(config: HeightmapConfig) -> WorldPreset {
+        WorldPreset {
+            name: "heightmap".to_string(),
+            terrain: TerrainConfig {
+                water_level: config.sea_level,
+                ..TerrainConfig::default()
+            },
+            stages: vec![
+                StageEntry::new("heightmap", StageConfig::Heightmap(config)),
+                StageEntry::new("ores", StageConfig::Ores(default_ores())),
+            ],
+        }
+    }
+    pub fn build_stages(&self, seed: u32) -> Vec<(String, Box<dyn GenerationStage>)> {
+        self.stages
+            .iter()
+            .map(|entry| (entry.name.clone(), entry.stage.build(seed)))
+            .collect()
+    }
+}
+
+impl Default for TerrainConfig {
+    fn default() -> Self {
+        TerrainConfig {
+            octaves: 3,
+            persistence: 0.6,
+            water_level: (CHUNKSIZE * METACHUNKSIZE) as i32 / 3,
+        }
+    }
+}
+
+impl Default for WorldPreset {
+    fn default() -> Self {
+        let world_height = (CHUNKSIZE * METACHUNKSIZE) as i32;
+        WorldPreset {
+            name: "default".to_string(),
+            terrain: TerrainConfig::default(),
+            stages: vec![
+                StageEntry::new("landmass", StageConfig::Landmass),
+                StageEntry::new("ores", StageConfig::Ores(default_ores())),
+                StageEntry::new(
+                    "caves",
+                    StageConfig::Caves {
+                        cheese_scale: 32.0,
+                        cheese_threshold: 0.45,
+                        worm_scale: 24.0,
+                        worm_width: 0.06,
+                        seal_depth: 4,
+                    },
+                ),
+                StageEntry::new("water", StageConfig::Water),
+                StageEntry::new(
+                    "grass",
+                    StageConfig::Grass {
+                        max_height: (world_height as f32 * 0.8) as i32,
+                    },
+                ),
+                StageEntry::new(
+                    "sand blob",
+                    StageConfig::SandBlob {
+                        offset_x: 20,
+                        offset_z: 20,
+                        depth: 5,
+                    },
+                ),
+                StageEntry::new(
+                    "square",
+                    StageConfig::Square {
+                        offset_x: 3,
+                        offset_z: 60,
+                        size: 10,
+                    },
+                ),
+                StageEntry::new(
+                    "trees",
+                    StageConfig::Trees {
+                        count: 300,
+                        spread: 50.0,
+                        species: default_tree_species(),
+                    },
+                ),
+                StageEntry::new("debug pillar", StageConfig::DebugPillar { height: 10 }),
+            ],
+        }
+    }
+}
+
+impl StageEntry {
+    pub fn new(name: &str, stage: StageConfig) -> StageEntry {
+        StageEntry {
+            name: name.to_string(),
+            stage,
+        }
+    }
+}
+
+impl StageConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            StageConfig::DebugGrid { spacing, .. } if *spacing <= 0 => {
+                Err(format!("grid spacing has to be positive, got {}", spacing))
+            }
+            StageConfig::Trees { spread, .. } if !(spread.is_finite() && *spread >= 0.0) => {
+                Err(format!("tree spread has to be a finite number of at least 0, got {}", spread))
+            }
+            StageConfig::Trees { species, .. } => species.iter().try_for_each(|s| s.validate()),
+            StageConfig::Ores(ores) => ores.iter().try_for_each(|ore| ore.validate()),
+            StageConfig::Heightmap(config) => config.validate(),
+            _ => Ok(()),
+        }
+    }
+    pub fn build(&self, seed: u32) -> Box<dyn GenerationStage> {
+        match self {
+            StageConfig::Landmass => Box::new(LandmassStage),
+            StageConfig::Caves {
+                cheese_scale,
+                cheese_threshold,
+                worm_scale,
+                worm_width,
+                seal_depth,
+            } => Box::new(CaveStage::new(
+                seed,
+                *cheese_scale,
+                *cheese_threshold,
+                *worm_scale,
+                *worm_width,
+                *seal_depth,
+            )),
+            StageConfig::Ores(ores) => Box::new(OreStage { ores: ores.clone() }),
+            StageConfig::Water => Box::new(WaterStage),
+            StageConfig::Grass { max_height } => Box::new(GrassStage {
+                max_height: *max_height,
+            }),
+            StageConfig::SandBlob {
+                offset_x,
+                offset_z,
+                depth,
+            } => Box::new(SandBlobStage {
+                offset_x: *offset_x,
+                offset_z: *offset_z,
+                depth: *depth,
+            }),
+            StageConfig::Square {
+                offset_x,
+                offset_z,
+                size,
+            } => Box::new(SquareStage {
+                offset_x: *offset_x,
+                offset_z: *offset_z,
+                size: *size,
+            }),
+            StageConfig::Trees {
+                count,
+                spread,
+                species,
+            } => Box::new(TreeStage {
+                count: *count,
+                spread: *spread,
+                species: species.clone(),
+            }),
+            StageConfig::DebugPillar { height } => Box::new(DebugPillarStage { height: *height }),
+            StageConfig::FlatLayers(layers) => Box::new(FlatLayersStage {
+                layers: layers.clone(),
+            }),
+            StageConfig::SpawnPlatform { block, radius, y } => Box::new(SpawnPlatformStage {
+                block: *block,
+                radius: *radius,
+                y: *y,
+            }),
+            StageConfig::DebugGrid { spacing, y } => Box::new(DebugGridStage {
+                spacing: *spacing,
+                y: *y,
+            }),
+            StageConfig::Heightmap(config) => Box::new(HeightmapStage::new(config.clone())),
+            StageConfig::Structure {
+                template,
+                offset_x,
+                offset_z,
+                placement,
+                random_rotation,
+            } => {
+                let loaded = match StructureTemplate::load_by_name(template) {
+                    Ok(loaded) => Some(loaded),
+                    Err(e) => {
+                        println!("could not load structure template {}: {}, skipping it", template, e);
+                        None
+                    }
+                };
+                Box::new(StructureStage {
+                    template: loaded,
+                    offset_x: *offset_x,
+                    offset_z: *offset_z,
+                    placement: *placement,
+                    random_rotation: *random_rotation,
+                })
+            }
+        }
+    }
+}
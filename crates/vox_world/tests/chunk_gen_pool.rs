@@ -0,0 +1,68 @@
+use std::time::{Duration, Instant};
+use vox_core::positions::MetaChunkPos;
+use vox_world::world_gen::chunk_gen_pool::{ChunkGenMetrics, ChunkGenPool};
+use vox_world::world_gen::world_preset::{StageConfig, StageEntry, WorldPreset};
+
+fn wait_for(pool: &ChunkGenPool, done: impl Fn(&ChunkGenMetrics) -> bool) -> ChunkGenMetrics {
+    let timer = Instant::now();
+    loop {
+        let metrics = pool.metrics();
+        if done(&metrics) {
+            return metrics;
+        }
+        assert!(timer.elapsed() < Duration::from_secs(10), "the pool did not finish: {:?}", metrics);
+        std::thread::sleep(Duration::from_millis(1));
+    }
+}
+
+#[test]
+fn requests_are_generated_closest_first() {
+    let pool = ChunkGenPool::new(WorldPreset::void(), 1);
+    pool.pause();
+    let far = MetaChunkPos { x: 3, z: 0 };
+    let near = MetaChunkPos { x: 0, z: -1 };
+    let tied = MetaChunkPos { x: 1, z: 0 };
+    let outside = MetaChunkPos { x: 9, z: 9 };
+    for pos in [far, near, tied, outside, near].iter() {
+        pool.request(*pos, 7);
+    }
+    assert_eq!(pool.metrics().queue_depth, 4);
+    assert_eq!(pool.update_center(MetaChunkPos { x: 0, z: 0 }, 4), vec![outside]);
+    pool.resume();
+
+    // near and tied are at the same distance, near was requested first
+    for expected in [near, tied, far].iter() {
+        let (chunk, pos) = pool.get_blocking().unwrap();
+        assert_eq!(pos, *expected);
+        assert_eq!(chunk.pos, *expected);
+    }
+    let metrics = wait_for(&pool, |m| m.generated == 3);
+    assert_eq!((metrics.queue_depth, metrics.in_progress, metrics.cancelled, metrics.failed), (0, 0, 1, 0));
+    assert!(pool.get().is_err());
+
+    // generated and cancelled positions can be requested again
+    pool.pause();
+    pool.request(outside, 7);
+    pool.request(near, 7);
+    pool.resume();
+    assert_eq!(pool.get_blocking().unwrap().1, near);
+    assert_eq!(pool.get_blocking().unwrap().1, outside);
+}
+
+#[test]
+fn panicking_generation_keeps_the_worker_running() {
+    // a grid without spacing divides by 0, presets from files are validated before they get here
+    let mut preset = WorldPreset::debug_grid();
+    preset.stages[1] = StageEntry::new("grid", StageConfig::DebugGrid { spacing: 0, y: 2 });
+    let pool = ChunkGenPool::new(preset, 1);
+    let pos = MetaChunkPos { x: 0, z: 0 };
+    pool.request(pos, 7);
+    let metrics = wait_for(&pool, |m| m.failed == 1);
+    assert_eq!((metrics.in_progress, metrics.generated), (0, 0));
+
+    // the position is not stuck in the queue
+    pool.request(pos, 7);
+    let metrics = wait_for(&pool, |m| m.failed == 2);
+    assert_eq!((metrics.in_progress, metrics.queue_depth), (0, 0));
+    assert!(pool.get().is_err());
+}
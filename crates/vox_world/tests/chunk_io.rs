@@ -0,0 +1,133 @@
+use std::time::{Duration, Instant};
+use vox_core::positions::{GlobalBlockPos, MetaChunkPos};
+use vox_world::world::chunk_io_thread::{ChunkIoResult, ChunkIoThread};
+use vox_world::world::small_world::SmallWorld;
+use vox_world::world_gen::basic::ChunkGenerator;
+use vox_world::world_gen::meta_chunk::MetaChunk;
+use vox_world::world_gen::world_preset::WorldPreset;
+
+const SEED: u32 = 7;
+const POS: MetaChunkPos = MetaChunkPos { x: 2, z: -1 };
+const EDIT: GlobalBlockPos = GlobalBlockPos { x: 140, y: 40, z: -20 };
+
+// the save directory is below a plain file, so every save fails until the file is removed
+fn blocked_save_dir(name: &str) -> (String, String) {
+    let blocker = std::env::temp_dir().join(name);
+    let _ = std::fs::remove_dir_all(&blocker);
+    std::fs::write(&blocker, b"not a directory").unwrap();
+    let save_dir = blocker.join("world");
+    return (
+        blocker.to_str().unwrap().to_string(),
+        save_dir.to_str().unwrap().to_string(),
+    );
+}
+
+fn edited_chunk() -> MetaChunk {
+    let generator = ChunkGenerator::from_preset(SEED, &WorldPreset::void());
+    let mut chunk = MetaChunk::generate(POS, &generator);
+    chunk.set_block(&EDIT, 11);
+    return chunk;
+}
+
+fn next_result(io: &ChunkIoThread) -> ChunkIoResult {
+    let timer = Instant::now();
+    loop {
+        if let Ok(result) = io.get() {
+            return result;
+        }
+        assert!(timer.elapsed() < Duration::from_secs(10), "no result from the io thread");
+        std::thread::sleep(Duration::from_millis(1));
+    }
+}
+
+fn expect_save_failed(io: &ChunkIoThread) {
+    io.flush();
+    match next_result(io) {
+        ChunkIoResult::SaveFailed(pos, _) => assert_eq!(pos, POS),
+        _ => panic!("expected the save to fail"),
+    }
+    assert_eq!(io.failed_saves(), 1);
+    assert_eq!(io.metrics().saved, 0);
+}
+
+#[test]
+fn failed_saves_are_retried() {
+    let (blocker, save_dir) = blocked_save_dir("vox_chunk_io_retry");
+    let io = ChunkIoThread::new(&save_dir);
+    io.save(POS, edited_chunk().serialize(&save_dir).unwrap());
+    expect_save_failed(&io);
+
+    std::fs::remove_file(&blocker).unwrap();
+    io.retry_failed_saves();
+    io.flush();
+    match next_result(&io) {
+        ChunkIoResult::Saved(pos, _) => assert_eq!(pos, POS),
+        _ => panic!("expected the retry to succeed"),
+    }
+    assert_eq!(io.failed_saves(), 0);
+    assert_eq!((io.metrics().saved, io.metrics().failed), (1, 1));
+    let saved = MetaChunk::load(&save_dir, POS, SEED).unwrap();
+    assert_eq!(saved.get_block(&EDIT), 11);
+    std::fs::remove_dir_all(&blocker).unwrap();
+}
+
+#[test]
+fn unloaded_chunks_with_failed_saves_keep_their_edits() {
+    let (blocker, save_dir) = blocked_save_dir("vox_chunk_io_reload");
+    let io = ChunkIoThread::new(&save_dir);
+    io.save(POS, edited_chunk().serialize(&save_dir).unwrap());
+    expect_save_failed(&io);
+
+    // the metachunk was dropped after it was serialized, loading it again gives back the edit
+    io.load(POS, SEED);
+    match next_result(&io) {
+        ChunkIoResult::Loaded(chunk, pos) => {
+            assert_eq!(pos, POS);
+            assert_eq!(chunk.get_block(&EDIT), 11);
+            assert!(chunk.modified);
+        }
+        _ => panic!("expected the unsaved metachunk"),
+    }
+    std::fs::remove_file(&blocker).unwrap();
+}
+
+#[test]
+fn newer_saves_replace_failed_ones() {
+    let (blocker, save_dir) = blocked_save_dir("vox_chunk_io_newer");
+    let io = ChunkIoThread::new(&save_dir);
+    io.save(POS, edited_chunk().serialize(&save_dir).unwrap());
+    expect_save_failed(&io);
+
+    std::fs::remove_file(&blocker).unwrap();
+    let mut newer = edited_chunk();
+    newer.set_block(&EDIT, 3);
+    io.save(POS, newer.serialize(&save_dir).unwrap());
+    assert_eq!(io.failed_saves(), 0);
+    io.retry_failed_saves();
+    io.flush();
+    let saved = MetaChunk::load(&save_dir, POS, SEED).unwrap();
+    assert_eq!(saved.get_block(&EDIT), 3);
+    std::fs::remove_dir_all(&blocker).unwrap();
+}
+
+#[test]
+fn dropping_the_world_writes_its_edits() {
+    let save_dir = std::env::temp_dir().join("vox_chunk_io_drop");
+    let _ = std::fs::remove_dir_all(&save_dir);
+    let save_dir = save_dir.to_str().unwrap().to_string();
+    let preset = WorldPreset::void();
+    let generator = ChunkGenerator::from_preset(SEED, &preset);
+    let mut world = SmallWorld::new(SEED, preset);
+    world.save_dir = save_dir.clone();
+    world.load_area(&generator, &EDIT, &EDIT);
+    world.set_block(11, EDIT);
+
+    // the same teardown as closing the game, without waiting for the saves
+    let io = ChunkIoThread::new(&save_dir);
+    io.save_modified_chunks(&mut world);
+    drop(world);
+    drop(io);
+    let saved = MetaChunk::load(&save_dir, POS, SEED).unwrap();
+    assert_eq!(saved.get_block(&EDIT), 11);
+    std::fs::remove_dir_all(&save_dir).unwrap();
+}
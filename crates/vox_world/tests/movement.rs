@@ -0,0 +1,218 @@
+use vox_core::positions::{GlobalBlockPos, MetaChunkPos, ObjectPos};
+use vox_world::blocks::block_type::BlockType;
+use vox_world::physics::movement::{MovementConfig, MovementInput, MovementMode};
+use vox_world::player::{Player, EYE_HEIGHT};
+use vox_world::world::small_world::SmallWorld;
+use vox_world::world_gen::basic::ChunkGenerator;
+use vox_world::world_gen::meta_chunk::MetaChunk;
+use vox_world::world_gen::preset_stages::FlatLayer;
+use vox_world::world_gen::world_preset::WorldPreset;
+
+const DT: f32 = 1.0 / 60.0;
+const FLOOR_Y: i32 = 10;
+// top of the floor, where the feet of a standing player are
+const GROUND: f32 = FLOOR_Y as f32 + 1.0;
+
+// one superflat metachunk of stone up to FLOOR_Y, everything around it is not loaded and collides
+fn flat_world() -> SmallWorld {
+    let preset = WorldPreset::superflat(vec![FlatLayer {
+        block: BlockType::Stone,
+        thickness: FLOOR_Y as u32 + 1,
+    }]);
+    let generator = ChunkGenerator::from_preset(7, &preset);
+    let mut world = SmallWorld::new(7, preset);
+    let pos = MetaChunkPos { x: 0, z: 0 };
+    world.add_chunk(pos, MetaChunk::generate(pos, &generator));
+    return world;
+}
+
+fn player_at(x: f32, feet: f32, z: f32) -> Player {
+    let mut player = Player::new();
+    player.position = ObjectPos {
+        x,
+        y: feet + EYE_HEIGHT,
+        z,
+    };
+    return player;
+}
+
+fn feet(player: &Player) -> f32 {
+    player.position.y - EYE_HEIGHT
+}
+
+fn simulate(player: &mut Player, world: &SmallWorld, input: MovementInput, seconds: f32) {
+    player.movement_input = input;
+    for _ in 0..(seconds / DT).round() as u32 {
+        player.update(&DT, world);
+    }
+}
+
+fn walk(x: f32, z: f32) -> MovementInput {
+    MovementInput {
+        wish: [x, 0.0, z],
+        jump: false,
+    }
+}
+
+fn jump() -> MovementInput {
+    MovementInput {
+        wish: [0.0; 3],
+        jump: true,
+    }
+}
+
+#[test]
+fn falls_and_lands_on_the_ground() {
+    let world = flat_world();
+    let mut player = player_at(32.5, 30.0, 32.5);
+    simulate(&mut player, &world, MovementInput::default(), 0.2);
+    assert!(feet(&player) < 30.0);
+    assert!(!player.movement.on_ground);
+    simulate(&mut player, &world, MovementInput::default(), 2.0);
+    assert!((feet(&player) - GROUND).abs() < 1e-3);
+    assert!(player.movement.on_ground);
+    assert_eq!(player.movement.velocity[1], 0.0);
+}
+
+#[test]
+fn accelerates_to_walk_speed_and_stops_with_friction() {
+    let world = flat_world();
+    let mut player = player_at(10.5, GROUND, 32.5);
+    simulate(&mut player, &world, MovementInput::default(), 0.1);
+    simulate(&mut player, &world, walk(1.0, 0.0), DT);
+    let walk_speed = player.movement.config.walk_speed;
+    assert!(player.movement.velocity[0] > 0.0 && player.movement.velocity[0] < walk_speed);
+    simulate(&mut player, &world, walk(1.0, 0.0), 1.0);
+    assert!((player.movement.velocity[0] - walk_speed).abs() < 1e-3);
+    simulate(&mut player, &world, MovementInput::default(), 0.5);
+    assert_eq!(player.movement.velocity[0], 0.0);
+    assert!(player.position.x > 14.0);
+}
+
+#[test]
+fn jumps_high_enough_for_a_single_block() {
+    let world = flat_world();
+    let mut player = player_at(32.5, GROUND, 32.5);
+    simulate(&mut player, &world, MovementInput::default(), 0.1);
+    player.movement_input = jump();
+    let mut highest = feet(&player);
+    for _ in 0..60 {
+        player.update(&DT, &world);
+        player.movement_input = MovementInput::default();
+        highest = highest.max(feet(&player));
+    }
+    assert!(highest > GROUND + 1.0);
+    assert!(highest < GROUND + 2.0);
+    assert!(player.movement.on_ground);
+    assert!((feet(&player) - GROUND).abs() < 1e-3);
+}
+
+#[test]
+fn cannot_jump_in_the_air_after_coyote_time() {
+    let world = flat_world();
+    // both players just left the ground and are high above the floor
+    let coyote_time = MovementConfig::default().coyote_time;
+    let mut late = player_at(32.5, 40.0, 32.5);
+    late.movement.on_ground = true;
+    simulate(&mut late, &world, MovementInput::default(), coyote_time * 2.0);
+    simulate(&mut late, &world, jump(), DT);
+    assert!(late.movement.velocity[1] < 0.0);
+
+    let mut early = player_at(32.5, 40.0, 32.5);
+    early.movement.on_ground = true;
+    simulate(&mut early, &world, MovementInput::default(), coyote_time / 2.0);
+    simulate(&mut early, &world, jump(), DT);
+    assert!(early.movement.velocity[1] > 0.0);
+}
+
+#[test]
+fn walls_stop_the_player_and_single_blocks_are_stepped_onto() {
+    let mut world = flat_world();
+    // a ledge of one block from x 20 on and a wall of two blocks on top of it at x 30
+    for z in 0..64 {
+        for x in 20..64 {
+            world.set_block(3, GlobalBlockPos { x, y: FLOOR_Y + 1, z });
+        }
+        world.set_block(3, GlobalBlockPos { x: 30, y: FLOOR_Y + 2, z });
+        world.set_block(3, GlobalBlockPos { x: 30, y: FLOOR_Y + 3, z });
+    }
+    let mut player = player_at(15.5, GROUND, 32.5);
+    simulate(&mut player, &world, walk(1.0, 0.0), 4.0);
+    assert!((feet(&player) - (GROUND + 1.0)).abs() < 1e-3);
+    assert!((player.position.x - (30.0 - 0.3)).abs() < 1e-3);
+    assert_eq!(player.movement.velocity[0], 0.0);
+
+    // sliding along the wall keeps the movement along it
+    let z = player.position.z;
+    simulate(&mut player, &world, walk(0.7, 0.7), 1.0);
+    assert!(player.position.z > z + 2.0);
+    assert!(player.position.x < 30.0);
+}
+
+#[test]
+fn fly_mode_ignores_gravity_and_noclip_ignores_blocks() {
+    let world = flat_world();
+    let mut player = player_at(32.5, 30.0, 32.5);
+    player.movement.set_mode(MovementMode::Fly);
+    simulate(&mut player, &world, MovementInput::default(), 1.0);
+    assert_eq!(feet(&player), 30.0);
+    let down = MovementInput {
+        wish: [0.0, -1.0, 0.0],
+        jump: false,
+    };
+    simulate(&mut player, &world, down, 3.0);
+    assert!((feet(&player) - GROUND).abs() < 1e-3);
+
+    player.movement.set_mode(player.movement.mode.next());
+    assert_eq!(player.movement.mode, MovementMode::Noclip);
+    simulate(&mut player, &world, down, 0.5);
+    assert!(feet(&player) < FLOOR_Y as f32);
+}
+
+// a pool of water from the floor up to water_top, the rest of the metachunk stays dry
+fn pool_world(water_top: i32) -> SmallWorld {
+    let mut world = flat_world();
+    for x in 0..32 {
+        for z in 0..64 {
+            for y in (FLOOR_Y + 1)..water_top {
+                world.set_block(1, GlobalBlockPos { x, y, z });
+            }
+        }
+    }
+    return world;
+}
+
+#[test]
+fn floats_at_the_surface_with_the_eyes_above_water() {
+    let water_top = FLOOR_Y + 20;
+    let world = pool_world(water_top);
+    let mut player = player_at(16.5, GROUND + 10.0, 32.5);
+    simulate(&mut player, &world, MovementInput::default(), DT);
+    assert!(player.fluid.eyes_underwater);
+    assert_eq!(player.fluid.submerged, 1.0);
+    simulate(&mut player, &world, MovementInput::default(), 10.0);
+    assert!(player.fluid.in_fluid());
+    assert!(!player.fluid.eyes_underwater);
+    assert!(feet(&player) < water_top as f32);
+    assert!(player.position.y > water_top as f32);
+    assert!(player.movement.velocity[1].abs() < 0.1);
+}
+
+#[test]
+fn water_slows_falls_and_jumping_swims_up() {
+    let water_top = FLOOR_Y + 20;
+    let world = pool_world(water_top);
+    let mut dry = player_at(48.5, GROUND + 15.0, 32.5);
+    let mut wet = player_at(16.5, GROUND + 15.0, 32.5);
+    wet.movement.velocity[1] = -10.0;
+    dry.movement.velocity[1] = -10.0;
+    simulate(&mut dry, &world, MovementInput::default(), 0.5);
+    simulate(&mut wet, &world, MovementInput::default(), 0.5);
+    assert!(wet.movement.velocity[1] > dry.movement.velocity[1]);
+    assert!(feet(&wet) > feet(&dry));
+
+    let y = wet.position.y;
+    simulate(&mut wet, &world, jump(), 1.0);
+    assert!(wet.position.y > y + 1.0);
+    assert!(wet.movement.velocity[1] <= wet.movement.config.swim_up_speed);
+}
@@ -0,0 +1,182 @@
+use vox_core::positions::{GlobalBlockPos, MetaChunkPos, ObjectPos};
+use vox_world::blocks::block::{get_blockid, is_solid, BlockId};
+use vox_world::blocks::block_type::BlockType;
+use vox_world::world::small_world::SmallWorld;
+use vox_world::world_gen::basic::ChunkGenerator;
+use vox_world::world_gen::meta_chunk::MetaChunk;
+use vox_world::world_gen::world_preset::WorldPreset;
+
+const STONE: BlockId = 3;
+const WATER: BlockId = 1;
+// a block on the negative side of every horizontal axis
+const TARGET: GlobalBlockPos = GlobalBlockPos { x: -9, y: 20, z: -13 };
+
+// the four metachunks around the origin, without any blocks
+fn empty_world() -> SmallWorld {
+    let preset = WorldPreset::superflat(Vec::new());
+    let generator = ChunkGenerator::from_preset(7, &preset);
+    let mut world = SmallWorld::new(7, preset);
+    for x in -1..=0 {
+        for z in -1..=0 {
+            let pos = MetaChunkPos { x, z };
+            world.add_chunk(pos, MetaChunk::generate(pos, &generator));
+        }
+    }
+    return world;
+}
+
+fn offset(pos: &GlobalBlockPos, d: [i32; 3], scale: i32) -> GlobalBlockPos {
+    pos.get_diff(d[0] * scale, d[1] * scale, d[2] * scale)
+}
+
+// steps along the ray in tiny increments, slow but obviously right away from block edges
+fn march(world: &SmallWorld, origin: &ObjectPos, dir: [f32; 3], max_dist: f32) -> Option<(GlobalBlockPos, f32)> {
+    let length = (dir[0] * dir[0] + dir[1] * dir[1] + dir[2] * dir[2]).sqrt();
+    let step = 0.001;
+    let mut t = 0.0;
+    while t <= max_dist {
+        let pos = GlobalBlockPos {
+            x: (origin.x + dir[0] / length * t).floor() as i32,
+            y: (origin.y + dir[1] / length * t).floor() as i32,
+            z: (origin.z + dir[2] / length * t).floor() as i32,
+        };
+        if is_solid(world.get_block(pos)) {
+            return Some((pos, t));
+        }
+        t += step;
+    }
+    return None;
+}
+
+#[test]
+fn axis_aligned_rays_hit_the_facing_side() {
+    let mut world = empty_world();
+    world.set_block(STONE, TARGET);
+    let directions = [[1, 0, 0], [-1, 0, 0], [0, 1, 0], [0, -1, 0], [0, 0, 1], [0, 0, -1]];
+    for d in directions {
+        let origin = offset(&TARGET, d, -4);
+        let dir = d.map(|v| v as f32);
+        let hit = world.raycast(origin.get_block_centre(), dir, 10.0, is_solid).unwrap();
+        assert_eq!(hit.block, TARGET);
+        assert_eq!(hit.block_id, STONE);
+        assert_eq!(hit.normal, d.map(|v| -v));
+        assert!((hit.distance - 3.5).abs() < 1e-5, "{:?} {}", d, hit.distance);
+        assert_eq!(hit.previous, offset(&TARGET, d, -1));
+
+        // starting on a block boundary
+        let boundary = ObjectPos {
+            x: TARGET.x as f32 + 0.5 - d[0] as f32 * 2.5,
+            y: TARGET.y as f32 + 0.5 - d[1] as f32 * 2.5,
+            z: TARGET.z as f32 + 0.5 - d[2] as f32 * 2.5,
+        };
+        let hit = world.raycast(boundary, dir, 10.0, is_solid).unwrap();
+        assert_eq!(hit.block, TARGET);
+        assert!((hit.distance - 2.0).abs() < 1e-5);
+    }
+}
+
+#[test]
+fn diagonal_rays_through_corners_hit_the_target() {
+    let mut world = empty_world();
+    world.set_block(STONE, TARGET);
+    for dx in [-1, 1] {
+        for dy in [-1, 1] {
+            for dz in [-1, 1] {
+                let d = [dx, dy, dz];
+                let origin = offset(&TARGET, d, -3);
+                let hit = world.raycast(origin.get_block_centre(), d.map(|v| v as f32), 10.0, is_solid).unwrap();
+                assert_eq!(hit.block, TARGET);
+                assert!((hit.distance - 2.5 * 3f32.sqrt()).abs() < 1e-4);
+                assert_eq!(hit.normal.iter().map(|v| v.abs()).sum::<i32>(), 1);
+                assert_eq!(hit.previous, hit.block.get_diff(hit.normal[0], hit.normal[1], hit.normal[2]));
+            }
+        }
+    }
+}
+
+#[test]
+fn rays_in_every_direction_match_marching() {
+    let mut world = empty_world();
+    // a hollow box of stone around an origin in negative coordinates
+    let centre_block = GlobalBlockPos { x: -20, y: 30, z: -5 };
+    let radius: i32 = 6;
+    for x in -radius..=radius {
+        for y in -radius..=radius {
+            for z in -radius..=radius {
+                if x.abs() == radius || y.abs() == radius || z.abs() == radius {
+                    world.set_block(STONE, centre_block.get_diff(x, y, z));
+                }
+            }
+        }
+    }
+    let origin = ObjectPos {
+        x: centre_block.x as f32 + 0.3,
+        y: centre_block.y as f32 + 0.6,
+        z: centre_block.z as f32 + 0.45,
+    };
+    for i in 0..24 {
+        for j in 1..12 {
+            let yaw = i as f32 / 24.0 * std::f32::consts::PI * 2.0 + 0.01;
+            let pitch = j as f32 / 12.0 * std::f32::consts::PI - std::f32::consts::FRAC_PI_2;
+            let dir = [yaw.cos() * pitch.cos(), pitch.sin(), yaw.sin() * pitch.cos()];
+            let start = ObjectPos { ..origin };
+            let hit = world.raycast(start, dir, 20.0, is_solid).unwrap();
+            let (block, distance) = march(&world, &origin, dir, 20.0).unwrap();
+            assert_eq!(hit.block, block, "dir {:?}", dir);
+            assert!((hit.distance - distance).abs() < 0.003, "dir {:?}", dir);
+            assert_eq!(hit.previous, hit.block.get_diff(hit.normal[0], hit.normal[1], hit.normal[2]));
+            assert!(!is_solid(world.get_block(hit.previous)));
+        }
+    }
+}
+
+#[test]
+fn rays_cross_zero_into_negative_blocks() {
+    let mut world = empty_world();
+    let block = GlobalBlockPos { x: -1, y: 20, z: 0 };
+    world.set_block(STONE, block);
+    let from_positive = ObjectPos { x: 0.5, y: 20.5, z: 0.5 };
+    let hit = world.raycast(from_positive, [-1.0, 0.0, 0.0], 5.0, is_solid).unwrap();
+    assert_eq!(hit.block, block);
+    assert_eq!(hit.normal, [1, 0, 0]);
+    assert!((hit.distance - 0.5).abs() < 1e-5);
+    assert_eq!(hit.previous, GlobalBlockPos { x: 0, y: 20, z: 0 });
+
+    // exactly on the boundary between the blocks
+    let on_boundary = ObjectPos { x: 0.0, y: 20.5, z: 0.5 };
+    let hit = world.raycast(on_boundary, [-1.0, 0.0, 0.0], 5.0, is_solid).unwrap();
+    assert_eq!(hit.block, block);
+    assert_eq!(hit.distance, 0.0);
+}
+
+#[test]
+fn max_distance_and_filters_limit_the_hits() {
+    let mut world = empty_world();
+    world.set_block(STONE, TARGET);
+    let water = TARGET.get_diff(-2, 0, 0);
+    world.set_block(WATER, water);
+    let origin = TARGET.get_diff(-5, 0, 0);
+    let dir = [1.0, 0.0, 0.0];
+    assert!(world.raycast(origin.get_block_centre(), dir, 4.4, is_solid).is_none());
+    assert_eq!(world.raycast(origin.get_block_centre(), dir, 4.6, is_solid).unwrap().block, TARGET);
+    let air = get_blockid(BlockType::Air);
+    let hit = world.raycast(origin.get_block_centre(), dir, 10.0, |b| b != air).unwrap();
+    assert_eq!(hit.block, water);
+    assert_eq!(hit.block_id, WATER);
+}
+
+#[test]
+fn rays_starting_inside_a_block_or_without_direction() {
+    let mut world = empty_world();
+    world.set_block(STONE, TARGET);
+    let hit = world.raycast(TARGET.get_block_centre(), [0.0, 1.0, 0.0], 10.0, is_solid).unwrap();
+    assert_eq!(hit.block, TARGET);
+    assert_eq!(hit.normal, [0, 0, 0]);
+    assert_eq!(hit.distance, 0.0);
+    assert_eq!(hit.previous, TARGET);
+
+    assert!(world.raycast(TARGET.get_block_centre(), [0.0, 0.0, 0.0], 10.0, is_solid).is_none());
+    // leaves the world through the top without hitting anything
+    let above = ObjectPos { x: 0.5, y: 60.0, z: 0.5 };
+    assert!(world.raycast(above, [0.0, 1.0, 0.0], 1000.0, |_| false).is_none());
+}
@@ -0,0 +1,232 @@
+use vox_core::positions::{GlobalBlockPos, MetaChunkPos};
+use vox_io::io::error::VoxIoError;
+use vox_io::io::file_reader::{read_from_file_with_migrations, read_meta_chunk_from_file};
+use vox_io::io::compression::Codec;
+use vox_io::io::file_writer::{write_to_file, write_to_file_with_codec};
+use vox_io::io::save_format::{
+    quarantine_file, recompress_file, split_header, Migrations, FORMAT_VERSION, HEADER_SIZE, MAGIC, QUARANTINE_DIR,
+};
+use vox_world::world::level_data::{LevelData, LEVEL_DATA_BACKUP_FILE, LEVEL_DATA_FILE};
+use vox_world::world_gen::column_heightmaps::HeightmapKind;
+use vox_world::world_gen::meta_chunk::MetaChunk;
+use vox_world::world_gen::world_preset::WorldPreset;
+
+// the fixtures hold metachunk 1, -2 of the void preset with seed 7 and three placed blocks,
+// every format version gets its own fixture so old saves keep loading
+fn fixture(version: u32) -> String {
+    format!("{}/tests/fixtures/metachunk_v{}.bin", env!("CARGO_MANIFEST_DIR"), version)
+}
+
+fn check_fixture_chunk(chunk: &MetaChunk) {
+    assert_eq!(chunk.pos, MetaChunkPos { x: 1, z: -2 });
+    assert_eq!(chunk.seed, 7);
+    assert_eq!(chunk.get_block(&GlobalBlockPos { x: 70, y: 2, z: -120 }), 3);
+    assert_eq!(chunk.get_block(&GlobalBlockPos { x: 71, y: 40, z: -100 }), 11);
+    assert_eq!(chunk.get_block(&GlobalBlockPos { x: 127, y: 63, z: -65 }), 1);
+    assert_eq!(chunk.get_block(&GlobalBlockPos { x: 70, y: 3, z: -120 }), 5);
+    assert_eq!(chunk.get_height(HeightmapKind::MotionBlocking, 71, -100), 40);
+    assert_eq!(chunk.get_height(HeightmapKind::OceanFloor, 127, -65), -1);
+}
+
+#[test]
+fn loads_every_previous_version() {
+    for version in 0..=FORMAT_VERSION {
+        let chunk: MetaChunk = read_meta_chunk_from_file(&fixture(version)).unwrap();
+        check_fixture_chunk(&chunk);
+    }
+}
+
+#[test]
+fn written_files_have_the_current_header() {
+    let chunk: MetaChunk = read_meta_chunk_from_file(&fixture(0)).unwrap();
+    let filename = std::env::temp_dir().join("vox_save_format_test.bin");
+    let filename = filename.to_str().unwrap();
+    write_to_file(filename, &chunk).unwrap();
+    let data = std::fs::read(filename).unwrap();
+    assert_eq!(data[..4], MAGIC);
+    assert_eq!(split_header(&data).unwrap().0, FORMAT_VERSION);
+    let reloaded: MetaChunk = read_meta_chunk_from_file(filename).unwrap();
+    check_fixture_chunk(&reloaded);
+    std::fs::remove_file(filename).unwrap();
+}
+
+#[test]
+fn every_codec_round_trips() {
+    let chunk: MetaChunk = read_meta_chunk_from_file(&fixture(0)).unwrap();
+    let filename = std::env::temp_dir().join("vox_save_format_codec.bin");
+    let filename = filename.to_str().unwrap();
+    for codec in Codec::ALL.iter() {
+        let stats = write_to_file_with_codec(filename, &chunk, *codec).unwrap();
+        assert_eq!(stats.codec, *codec);
+        assert_eq!(std::fs::read(filename).unwrap()[12], codec.id());
+        let reloaded: MetaChunk = read_meta_chunk_from_file(filename).unwrap();
+        check_fixture_chunk(&reloaded);
+    }
+    std::fs::remove_file(filename).unwrap();
+}
+
+#[test]
+fn recompress_upgrades_old_files() {
+    let filename = std::env::temp_dir().join("vox_save_format_recompress.bin");
+    let filename = filename.to_str().unwrap();
+    std::fs::copy(fixture(0), filename).unwrap();
+    let (before, after) = recompress_file::<MetaChunk>(filename, Codec::Zstd).unwrap();
+    assert_eq!(before.codec, Codec::None);
+    assert_eq!(before.raw_size, after.raw_size);
+    assert!(after.compressed_size < before.compressed_size);
+    let data = std::fs::read(filename).unwrap();
+    assert_eq!(split_header(&data).unwrap().0, FORMAT_VERSION);
+    let reloaded: MetaChunk = read_meta_chunk_from_file(filename).unwrap();
+    check_fixture_chunk(&reloaded);
+    std::fs::remove_file(filename).unwrap();
+}
+
+#[test]
+fn damaged_files_fail_the_checksum() {
+    let mut data = std::fs::read(fixture(FORMAT_VERSION)).unwrap();
+    let last = data.len() - 1;
+    data[last] ^= 0xff;
+    let filename = std::env::temp_dir().join("vox_save_format_damaged.bin");
+    let filename = filename.to_str().unwrap();
+    std::fs::write(filename, data).unwrap();
+    match read_meta_chunk_from_file::<MetaChunk>(filename) {
+        Err(VoxIoError::Corrupt(_, reason)) => assert_eq!(reason, "checksum mismatch"),
+        _ => panic!("expected a corrupt file"),
+    }
+    std::fs::remove_file(filename).unwrap();
+}
+
+#[test]
+fn damaged_headers_are_not_read_as_legacy_files() {
+    let data = std::fs::read(fixture(FORMAT_VERSION)).unwrap();
+    let filename = std::env::temp_dir().join("vox_save_format_damaged_header.bin");
+    let filename = filename.to_str().unwrap();
+    for damaged in [0, 3].iter() {
+        let mut data = data.clone();
+        data[*damaged] ^= 0x01;
+        std::fs::write(filename, &data).unwrap();
+        match read_meta_chunk_from_file::<MetaChunk>(filename) {
+            Err(VoxIoError::Corrupt(_, reason)) => assert_eq!(reason, "missing or damaged header"),
+            _ => panic!("expected a corrupt file"),
+        }
+    }
+    std::fs::write(filename, &data[..HEADER_SIZE - 1]).unwrap();
+    assert!(matches!(read_meta_chunk_from_file::<MetaChunk>(filename), Err(VoxIoError::Corrupt(..))));
+    std::fs::remove_file(filename).unwrap();
+}
+
+#[test]
+fn saves_leave_no_temporary_files() {
+    let chunk: MetaChunk = read_meta_chunk_from_file(&fixture(0)).unwrap();
+    let directory = std::env::temp_dir().join("vox_save_format_atomic");
+    let _ = std::fs::remove_dir_all(&directory);
+    std::fs::create_dir_all(&directory).unwrap();
+    let filename = directory.join("chunk.bin");
+    let filename = filename.to_str().unwrap();
+    write_to_file(filename, &chunk).unwrap();
+    write_to_file_with_codec(filename, &chunk, Codec::Lz4).unwrap();
+    let names: Vec<_> = std::fs::read_dir(&directory).unwrap().flatten().map(|e| e.file_name()).collect();
+    assert_eq!(names, vec!["chunk.bin"]);
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn failed_saves_leave_no_temporary_files() {
+    let chunk: MetaChunk = read_meta_chunk_from_file(&fixture(0)).unwrap();
+    let directory = std::env::temp_dir().join("vox_save_format_failed_write");
+    let _ = std::fs::remove_dir_all(&directory);
+    // the destination is a directory, so the rename fails after the data was written
+    std::fs::create_dir_all(directory.join("chunk.bin")).unwrap();
+    let filename = directory.join("chunk.bin");
+    assert!(matches!(write_to_file(filename.to_str().unwrap(), &chunk), Err(VoxIoError::Io(..))));
+    let names: Vec<_> = std::fs::read_dir(&directory).unwrap().flatten().map(|e| e.file_name()).collect();
+    assert_eq!(names, vec!["chunk.bin"]);
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn unreadable_level_data_does_not_replace_the_backup() {
+    let directory = std::env::temp_dir().join("vox_save_format_level_backup");
+    let _ = std::fs::remove_dir_all(&directory);
+    let directory = directory.to_str().unwrap();
+    let backup_seed = || {
+        let backup = format!("{}/{}", directory, LEVEL_DATA_BACKUP_FILE);
+        return read_meta_chunk_from_file::<LevelData>(&backup).unwrap().seed;
+    };
+    let mut level = LevelData::new(1, WorldPreset::void());
+    level.save(directory).unwrap();
+    level.seed = 2;
+    level.save(directory).unwrap();
+    assert_eq!(backup_seed(), 1);
+
+    std::fs::write(format!("{}/{}", directory, LEVEL_DATA_FILE), b"damaged").unwrap();
+    level.seed = 3;
+    level.save(directory).unwrap();
+    assert_eq!(backup_seed(), 1);
+    assert_eq!(LevelData::load(directory).unwrap().seed, 3);
+    level.seed = 4;
+    level.save(directory).unwrap();
+    assert_eq!(backup_seed(), 3);
+    std::fs::remove_dir_all(directory).unwrap();
+}
+
+#[test]
+fn quarantined_files_are_moved_aside() {
+    let directory = std::env::temp_dir().join("vox_save_format_quarantine");
+    let _ = std::fs::remove_dir_all(&directory);
+    std::fs::create_dir_all(&directory).unwrap();
+    let filename = directory.join("chunk.bin");
+    let filename = filename.to_str().unwrap();
+    std::fs::write(filename, [1, 2, 3]).unwrap();
+    let target = quarantine_file(filename).unwrap();
+    assert!(!std::path::Path::new(filename).exists());
+    assert!(target.contains(QUARANTINE_DIR));
+    assert_eq!(std::fs::read(&target).unwrap(), vec![1, 2, 3]);
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn newer_versions_are_rejected() {
+    let mut data = std::fs::read(fixture(FORMAT_VERSION)).unwrap();
+    data[4..8].copy_from_slice(&(FORMAT_VERSION + 1).to_le_bytes());
+    let filename = std::env::temp_dir().join("vox_save_format_future.bin");
+    let filename = filename.to_str().unwrap();
+    std::fs::write(filename, data).unwrap();
+    match read_meta_chunk_from_file::<MetaChunk>(filename) {
+        Err(VoxIoError::VersionMismatch { found, expected, .. }) => {
+            assert_eq!(found, FORMAT_VERSION + 1);
+            assert_eq!(expected, FORMAT_VERSION);
+        }
+        _ => panic!("expected a version mismatch"),
+    }
+    std::fs::remove_file(filename).unwrap();
+}
+
+#[test]
+fn missing_migration_is_a_version_mismatch() {
+    match read_from_file_with_migrations::<MetaChunk>(&fixture(0), &Migrations::new()) {
+        Err(VoxIoError::VersionMismatch { found: 0, .. }) => {}
+        _ => panic!("expected a version mismatch"),
+    }
+}
+
+#[test]
+fn migrations_run_in_order() {
+    let mut migrations = Migrations::new();
+    for from in 0..FORMAT_VERSION {
+        migrations.register(from, |mut payload| {
+            payload.push(payload.len() as u8);
+            Ok(payload)
+        });
+    }
+    let upgraded = migrations.upgrade("test", 0, Vec::new()).unwrap();
+    assert_eq!(upgraded, (0..FORMAT_VERSION as u8).collect::<Vec<u8>>());
+}
+
+#[test]
+fn missing_files_are_not_found() {
+    match read_meta_chunk_from_file::<MetaChunk>(&fixture(999)) {
+        Err(VoxIoError::NotFound(_)) => {}
+        _ => panic!("expected not found"),
+    }
+}
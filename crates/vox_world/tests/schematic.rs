@@ -0,0 +1,69 @@
+use vox_core::positions::GlobalBlockPos;
+use vox_world::structures::schematic::{export_schematic, paste_schematic, BlockNameMapping};
+use vox_world::world::small_world::SmallWorld;
+use vox_world::world_gen::basic::ChunkGenerator;
+use vox_world::world_gen::world_preset::WorldPreset;
+
+// a superflat world that never finds saved metachunks
+fn superflat_world() -> (SmallWorld, ChunkGenerator) {
+    let preset = WorldPreset::from_name("superflat");
+    let generator = ChunkGenerator::from_preset(7, &preset);
+    let mut world = SmallWorld::new(7, preset);
+    world.save_dir = std::env::temp_dir()
+        .join("vox_schematic_test_world_does_not_exist")
+        .to_str()
+        .unwrap()
+        .to_string();
+    return (world, generator);
+}
+
+#[test]
+fn exported_regions_paste_back_the_same() {
+    let (mut world, generator) = superflat_world();
+    let min = GlobalBlockPos { x: -3, y: 12, z: 60 };
+    let max = GlobalBlockPos { x: 2, y: 16, z: 66 };
+    world.load_area(&generator, &min, &max);
+    // a little tower on the grass that crosses into the next metachunks
+    for y in 14..17 {
+        world.set_block(11, GlobalBlockPos { x: -1, y, z: 64 });
+    }
+    world.set_block(6, GlobalBlockPos { x: 0, y: 16, z: 64 });
+    world.set_block(4, GlobalBlockPos { x: 2, y: 14, z: 60 });
+
+    let mapping = BlockNameMapping::default();
+    for version in [2, 3].iter() {
+        let filename = std::env::temp_dir().join(format!("vox_schematic_region_v{}.schem", version));
+        let filename = filename.to_str().unwrap();
+        export_schematic(&world, &min, &max, filename, &mapping, *version).unwrap();
+
+        let (mut copy, generator) = superflat_world();
+        let target = GlobalBlockPos { x: 100, y: 20, z: -40 };
+        paste_schematic(&mut copy, &generator, filename, &target, &mapping).unwrap();
+        for x in 0..6 {
+            for y in 0..5 {
+                for z in 0..7 {
+                    assert_eq!(
+                        copy.get_block(target.get_diff(x, y, z)),
+                        world.get_block(min.get_diff(x, y, z)),
+                        "{} {} {}",
+                        x,
+                        y,
+                        z
+                    );
+                }
+            }
+        }
+        std::fs::remove_file(filename).unwrap();
+    }
+}
+
+#[test]
+fn regions_longer_than_a_schematic_are_refused() {
+    let (world, _) = superflat_world();
+    let filename = std::env::temp_dir().join("vox_schematic_too_long.schem");
+    let filename = filename.to_str().unwrap();
+    let min = GlobalBlockPos { x: -40000, y: 0, z: 0 };
+    let max = GlobalBlockPos { x: 40000, y: 0, z: 0 };
+    assert!(export_schematic(&world, &min, &max, filename, &BlockNameMapping::default(), 3).is_err());
+    assert!(!std::path::Path::new(filename).exists());
+}
@@ -0,0 +1,79 @@
+use std::collections::HashMap;
+use vox_core::positions::GlobalBlockPos;
+use vox_world::blocks::block::{get_blockid, get_blocktype};
+use vox_world::blocks::block_type::BlockType;
+use vox_world::structures::template::{Placement, Rotation, StructureTemplate, TemplateBlock};
+use vox_world::world::small_world::SmallWorld;
+use vox_world::world_gen::basic::ChunkGenerator;
+use vox_world::world_gen::world_preset::WorldPreset;
+
+// an L on the ground with arms of different length and a leaf on its corner, the rest is kept
+fn l_template() -> StructureTemplate {
+    let mut blocks = HashMap::new();
+    blocks.insert((0, 0, 0), BlockType::Stone);
+    blocks.insert((1, 0, 0), BlockType::Dirt);
+    blocks.insert((2, 0, 0), BlockType::Sand);
+    blocks.insert((0, 0, 1), BlockType::Log);
+    blocks.insert((0, 1, 0), BlockType::Leaf);
+    return StructureTemplate::from_grid("l", [3, 2, 2], [0, 0, 0], |x, y, z| match blocks.get(&(x, y, z)) {
+        Some(block) => TemplateBlock::Place(*block),
+        None => TemplateBlock::Keep,
+    });
+}
+
+#[test]
+fn templates_rotate_and_mirror() {
+    let preset = WorldPreset::from_name("superflat");
+    let generator = ChunkGenerator::from_preset(7, &preset);
+    let mut world = SmallWorld::new(7, preset);
+    world.save_dir = std::env::temp_dir()
+        .join("vox_template_test_world_does_not_exist")
+        .to_str()
+        .unwrap()
+        .to_string();
+    world.load_area(&generator, &GlobalBlockPos { x: -5, y: 0, z: -5 }, &GlobalBlockPos { x: 200, y: 0, z: 5 });
+    let template = l_template();
+
+    // where the end of the x arm (2, 0) and the z arm (0, 1) end up, by rotation
+    let cases = [
+        (false, false, [[(2, 0), (0, 1)], [(0, 2), (-1, 0)], [(-2, 0), (0, -1)], [(0, -2), (1, 0)]]),
+        (true, false, [[(-2, 0), (0, 1)], [(0, -2), (-1, 0)], [(2, 0), (0, -1)], [(0, 2), (1, 0)]]),
+        (false, true, [[(2, 0), (0, -1)], [(0, 2), (1, 0)], [(-2, 0), (0, 1)], [(0, -2), (-1, 0)]]),
+    ];
+    let mut pos = GlobalBlockPos { x: 0, y: 14, z: 0 };
+    for (mirror_x, mirror_z, rotations) in cases.iter() {
+        for (rotation, [x_arm, z_arm]) in rotations.iter().enumerate() {
+            pos = pos.get_diff(10, 0, 0);
+            // water everywhere around the template, the kept positions have to stay water
+            for x in -2..=2 {
+                for z in -2..=2 {
+                    for y in 0..2 {
+                        world.set_block(get_blockid(BlockType::Water), pos.get_diff(x, y, z));
+                    }
+                }
+            }
+            let placement = Placement {
+                rotation: Rotation::from_index(rotation as u32),
+                mirror_x: *mirror_x,
+                mirror_z: *mirror_z,
+            };
+            template.place(&pos, &placement, &mut world);
+
+            let mut expected = HashMap::new();
+            expected.insert((0, 0, 0), BlockType::Stone);
+            expected.insert((x_arm.0 / 2, 0, x_arm.1 / 2), BlockType::Dirt);
+            expected.insert((x_arm.0, 0, x_arm.1), BlockType::Sand);
+            expected.insert((z_arm.0, 0, z_arm.1), BlockType::Log);
+            expected.insert((0, 1, 0), BlockType::Leaf);
+            for x in -2..=2 {
+                for z in -2..=2 {
+                    for y in 0..2 {
+                        let block = get_blocktype(world.get_block(pos.get_diff(x, y, z)));
+                        let expected = *expected.get(&(x, y, z)).unwrap_or(&BlockType::Water);
+                        assert_eq!(block, expected, "{:?} {} {} {}", placement, x, y, z);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use vox_core::positions::GlobalBlockPos;
+use vox_io::io::magica_voxel::{read_vox_from_file, write_vox_to_file, VoxModel, VoxScene};
+use vox_world::blocks::block::{get_blocktype, placeable_blocks};
+use vox_world::blocks::block_type::BlockType;
+use vox_world::structures::template::TemplateBlock;
+use vox_world::structures::vox_model::{
+    block_for_color, block_palette, export_vox_region, import_vox_models, import_vox_scene,
+};
+use vox_world::world::small_world::SmallWorld;
+use vox_world::world_gen::basic::ChunkGenerator;
+use vox_world::world_gen::world_preset::WorldPreset;
+
+fn scene(positions: &[[i32; 3]]) -> VoxScene {
+    VoxScene {
+        models: positions
+            .iter()
+            .map(|position| VoxModel {
+                size: [2, 1, 3],
+                voxels: vec![[0, 0, 0, 4], [1, 0, 2, 12]],
+                position: *position,
+            })
+            .collect(),
+        palette: block_palette(),
+    }
+}
+
+#[test]
+fn block_colors_round_trip() {
+    let palette = block_palette();
+    for id in placeable_blocks() {
+        assert_eq!(block_for_color(palette[id as usize + 1]), get_blocktype(id));
+    }
+}
+
+#[test]
+fn spread_out_scenes_are_imported_per_model() {
+    let filename = std::env::temp_dir().join("vox_model_spread.vox");
+    let filename = filename.to_str().unwrap();
+    write_vox_to_file(filename, &scene(&[[-10000, -10000, 0], [10000, 10000, 10000]])).unwrap();
+    assert!(import_vox_scene(filename).is_err());
+    let templates = import_vox_models(filename).unwrap();
+    assert_eq!(templates.len(), 2);
+    assert_eq!(templates[0].size(), [2, 3, 1]);
+
+    // close models are merged, vox files are z up
+    write_vox_to_file(filename, &scene(&[[0, 0, 0], [4, 0, 0]])).unwrap();
+    let template = import_vox_scene(filename).unwrap();
+    assert_eq!(template.size(), [6, 3, 1]);
+    assert_eq!(template.get(0, 0, 0), TemplateBlock::Place(get_blocktype(3)));
+    assert_eq!(template.get(5, 2, 0), TemplateBlock::Place(get_blocktype(11)));
+    assert_eq!(template.get(2, 0, 0), TemplateBlock::Keep);
+    std::fs::remove_file(filename).unwrap();
+}
+
+#[test]
+fn wide_regions_are_split_into_models() {
+    let preset = WorldPreset::from_name("superflat");
+    let generator = ChunkGenerator::from_preset(7, &preset);
+    let mut world = SmallWorld::new(7, preset);
+    world.save_dir = std::env::temp_dir()
+        .join("vox_model_test_world_does_not_exist")
+        .to_str()
+        .unwrap()
+        .to_string();
+    let min = GlobalBlockPos { x: -10, y: 12, z: 5 };
+    let max = GlobalBlockPos { x: 300, y: 15, z: 8 };
+    world.load_area(&generator, &min, &max);
+    world.set_block(11, GlobalBlockPos { x: 290, y: 14, z: 6 });
+    world.set_block(12, GlobalBlockPos { x: -10, y: 15, z: 8 });
+
+    let filename = std::env::temp_dir().join("vox_model_region.vox");
+    let filename = filename.to_str().unwrap();
+    export_vox_region(&world, &min, &max, filename).unwrap();
+    let scene = read_vox_from_file(filename).unwrap();
+    assert_eq!(scene.models.len(), 2);
+    assert_eq!(scene.models[0].size, [256, 4, 4]);
+    assert_eq!(scene.models[0].position, [0, 0, 0]);
+    assert_eq!(scene.models[1].size, [55, 4, 4]);
+    assert_eq!(scene.models[1].position, [256, 0, 0]);
+
+    let mut exported = 0;
+    for model in &scene.models {
+        for v in &model.voxels {
+            let pos = min.get_diff(model.position[0] + v[0] as i32, v[2] as i32, v[1] as i32);
+            let block = get_blocktype(world.get_block(pos.clone()));
+            assert_eq!(block_for_color(scene.palette[v[3] as usize]), block, "{:?}", pos);
+            exported += 1;
+        }
+    }
+    // grass and dirt below y 14, plus the two placed blocks
+    assert_eq!(exported, 311 * 4 * 2 + 2);
+    assert_ne!(get_blocktype(world.get_block(min.clone())), BlockType::Air);
+    std::fs::remove_file(filename).unwrap();
+}
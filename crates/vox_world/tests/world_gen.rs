@@ -0,0 +1,173 @@
+use vox_core::positions::{GlobalBlockPos, MetaChunkPos};
+use vox_io::io::error::VoxIoError;
+use vox_io::io::image_writer::write_png_to_file;
+use vox_world::blocks::block::get_blocktype;
+use vox_world::blocks::block_type::BlockType;
+use vox_world::world_gen::basic::ChunkGenerator;
+use vox_world::world_gen::column_heightmaps::HeightmapKind;
+use vox_world::world_gen::heightmap_stage::HeightmapConfig;
+use vox_world::world_gen::meta_chunk::MetaChunk;
+use vox_world::world_gen::ores::OreConfig;
+use vox_world::world_gen::structure_stages::WeightedSpecies;
+use vox_world::world_gen::world_preset::{StageConfig, StageEntry, WorldPreset};
+
+#[test]
+fn built_in_presets_are_valid() {
+    for preset in [
+        WorldPreset::default(),
+        WorldPreset::from_name("superflat"),
+        WorldPreset::void(),
+        WorldPreset::debug_grid(),
+    ]
+    .iter()
+    {
+        assert!(preset.validate().is_ok(), "{}", preset.name);
+    }
+}
+
+#[test]
+fn debug_grid_spacing_has_to_be_positive() {
+    for spacing in [0, -3].iter() {
+        let mut preset = WorldPreset::debug_grid();
+        preset.stages[1] = StageEntry::new("grid", StageConfig::DebugGrid { spacing: *spacing, y: 2 });
+        assert!(preset.validate().is_err());
+    }
+}
+
+#[test]
+fn tree_spread_has_to_be_finite_and_not_negative() {
+    for spread in [-1.0, f32::NAN, f32::INFINITY].iter() {
+        let mut preset = WorldPreset::default();
+        for entry in preset.stages.iter_mut() {
+            if let StageConfig::Trees { spread: s, .. } = &mut entry.stage {
+                *s = *spread;
+            }
+        }
+        assert!(preset.validate().is_err(), "{}", spread);
+    }
+}
+
+// sets the given field of the first tree species of the default preset
+fn preset_with_species(change: impl Fn(&mut WeightedSpecies)) -> WorldPreset {
+    let mut preset = WorldPreset::default();
+    for entry in preset.stages.iter_mut() {
+        if let StageConfig::Trees { species, .. } = &mut entry.stage {
+            change(&mut species[0]);
+        }
+    }
+    return preset;
+}
+
+#[test]
+fn tree_species_have_to_be_valid() {
+    assert!(preset_with_species(|s| s.weight = 0.0).validate().is_ok());
+    for weight in [-1.0, f32::NAN, f32::INFINITY].iter() {
+        assert!(preset_with_species(|s| s.weight = *weight).validate().is_err(), "{}", weight);
+    }
+    assert!(preset_with_species(|s| s.species.min_height = 0).validate().is_err());
+    assert!(preset_with_species(|s| s.species.max_height = s.species.min_height - 1).validate().is_err());
+    assert!(preset_with_species(|s| s.species.trunk_width = 0).validate().is_err());
+    assert!(preset_with_species(|s| s.species.canopy_radius = -2).validate().is_err());
+}
+
+fn preset_with_ore(change: impl Fn(&mut OreConfig)) -> WorldPreset {
+    let mut preset = WorldPreset::default();
+    for entry in preset.stages.iter_mut() {
+        if let StageConfig::Ores(ores) = &mut entry.stage {
+            change(&mut ores[0]);
+        }
+    }
+    return preset;
+}
+
+#[test]
+fn ores_have_to_be_valid() {
+    assert!(preset_with_ore(|o| o.frequency = 0.0).validate().is_ok());
+    for frequency in [-1.0, f32::NAN, f32::INFINITY, 1e9].iter() {
+        assert!(preset_with_ore(|o| o.frequency = *frequency).validate().is_err(), "{}", frequency);
+    }
+    assert!(preset_with_ore(|o| o.vein_size = 0).validate().is_err());
+    assert!(preset_with_ore(|o| o.vein_size = u32::MAX).validate().is_err());
+    assert!(preset_with_ore(|o| o.min_y = o.max_y + 1).validate().is_err());
+}
+
+#[test]
+fn heightmap_images_have_to_load() {
+    let directory = std::env::temp_dir().join("vox_heightmap_validation");
+    std::fs::create_dir_all(&directory).unwrap();
+    let image = directory.join("heightmap.png");
+    let image = image.to_str().unwrap();
+    let broken = directory.join("broken.png");
+    let broken = broken.to_str().unwrap();
+    let missing = directory.join("missing.png");
+    let missing = missing.to_str().unwrap();
+    write_png_to_file(image, 2, 2, &[[0, 0, 0, 255], [255, 255, 255, 255], [9, 9, 9, 255], [90, 90, 90, 255]]).unwrap();
+    std::fs::write(broken, b"not a png").unwrap();
+
+    assert!(WorldPreset::from_name(image).validate().is_ok());
+    assert!(WorldPreset::from_name(missing).validate().is_err());
+    assert!(WorldPreset::from_name(broken).validate().is_err());
+    assert!(WorldPreset::heightmap(HeightmapConfig::new(image, Some(image))).validate().is_ok());
+    assert!(WorldPreset::heightmap(HeightmapConfig::new(image, Some(missing))).validate().is_err());
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn invalid_preset_files_are_rejected() {
+    let filename = std::env::temp_dir().join("vox_invalid_preset.ron");
+    let filename = filename.to_str().unwrap();
+    std::fs::write(
+        filename,
+        "(name: \"grid\", terrain: (octaves: 3, persistence: 0.6, water_level: 21), \
+         stages: [(name: \"grid\", stage: DebugGrid(spacing: 0, y: 2))])",
+    )
+    .unwrap();
+    match WorldPreset::load(filename) {
+        Err(VoxIoError::Corrupt(_, _)) => {}
+        other => panic!("expected a corrupt preset, got {:?}", other.map(|p| p.name)),
+    }
+    std::fs::remove_file(filename).unwrap();
+}
+
+#[test]
+fn superflat_generates_its_layers() {
+    let generator = ChunkGenerator::from_preset(7, &WorldPreset::from_name("superflat"));
+    let chunk = MetaChunk::generate(MetaChunkPos { x: -1, z: 2 }, &generator);
+    for (x, z) in [(-64, 128), (-1, 191), (-30, 150)].iter() {
+        for y in 0..64 {
+            let expected = match y {
+                0..=9 => BlockType::Stone,
+                10..=12 => BlockType::Dirt,
+                13 => BlockType::Grass,
+                _ => BlockType::Air,
+            };
+            let block = chunk.get_block(&GlobalBlockPos { x: *x, y, z: *z });
+            assert_eq!(get_blocktype(block), expected, "{} {} {}", x, y, z);
+        }
+        assert_eq!(chunk.get_height(HeightmapKind::MotionBlocking, *x, *z), 13);
+    }
+}
+
+// the square and the trees of the default preset reach past the metachunk edge
+#[test]
+fn default_preset_generates_within_its_metachunk() {
+    let filename = format!("{}/../vox/presets/default.ron", env!("CARGO_MANIFEST_DIR"));
+    let preset = WorldPreset::load(&filename).unwrap();
+    let generator = ChunkGenerator::from_preset(7, &preset);
+    for pos in [MetaChunkPos { x: 0, z: 0 }, MetaChunkPos { x: -1, z: 3 }].iter() {
+        let chunk = MetaChunk::generate(*pos, &generator);
+        for x in (pos.x * 64)..(pos.x * 64 + 64) {
+            for z in (pos.z * 64)..(pos.z * 64 + 64) {
+                for kind in HeightmapKind::ALL.iter() {
+                    let highest = (0..64)
+                        .rev()
+                        .find(|y| kind.counts(chunk.get_block(&GlobalBlockPos { x, y: *y, z })))
+                        .unwrap_or(-1);
+                    assert_eq!(chunk.get_height(*kind, x, z), highest, "{:?} {} {}", kind, x, z);
+                }
+            }
+        }
+        assert_eq!(chunk.get_height(HeightmapKind::MotionBlocking, pos.x * 64 - 1, pos.z * 64), -1);
+        assert_eq!(chunk.get_height(HeightmapKind::MotionBlocking, pos.x * 64, pos.z * 64 + 64), -1);
+    }
+}